@@ -1,20 +1,136 @@
-pub(crate) mod v4 {
-    use lazy_static::lazy_static;
-    use reqwest::Client;
-    use std::time::Duration;
+use lazy_static::lazy_static;
+use reqwest::{header::HeaderMap, Client};
+use std::{
+    fmt,
+    future::Future,
+    str::FromStr,
+    sync::RwLock,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-    use super::ApiError;
+const USER_AGENT: &str = "giss";
 
-    const GITHUB_API_V4_URL: &str = "https://api.github.com/graphql";
-    const USER_AGENT: &str = "giss";
+/// Maximum number of times a query is sent when it is rate limited
+const MAX_ATTEMPTS: u32 = 3;
 
-    lazy_static! {
-        pub static ref CLIENT: Client = Client::builder()
-            .connect_timeout(Duration::from_secs(10))
-            .timeout(std::time::Duration::from_secs(15))
-            .build()
-            .unwrap();
+/// Longest time to wait before retrying a rate limited query. If the rate limit resets later
+/// than this, the query fails instead.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// GitHub recommends waiting at least one minute when a secondary rate limit is hit without
+/// a Retry-After header
+const SECONDARY_BACKOFF: Duration = Duration::from_secs(60);
+
+lazy_static! {
+    pub static ref CLIENT: Client = Client::builder()
+        .connect_timeout(Duration::from_secs(10))
+        .timeout(DEFAULT_TIMEOUT)
+        .build()
+        .unwrap();
+}
+
+/// Send a request with `send`, which is retried if it is rate limited (and `retry` is set) as
+/// long as the rate limit resets within `MAX_BACKOFF`
+async fn retry<T, F, Fut>(retry: bool, mut send: F) -> Result<T, ApiError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ApiError>>,
+{
+    let mut attempt: u32 = 1;
+    loop {
+        match send().await {
+            Err(ApiError::RateLimited(wait))
+                if retry && attempt < MAX_ATTEMPTS && wait <= MAX_BACKOFF =>
+            {
+                log::warn!(
+                    "GitHub API: rate limited, retrying in {}s ({}/{})",
+                    wait.as_secs(),
+                    attempt,
+                    MAX_ATTEMPTS
+                );
+                tokio::time::sleep(wait).await;
+                attempt += 1;
+            }
+            Err(ApiError::RateLimited(wait)) => {
+                log::error!("GitHub API: rate limited, try again in {}s", wait.as_secs());
+                return Err(ApiError::RateLimited(wait));
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Rate limit status, as given by the headers of a response
+#[derive(Debug)]
+struct Quota {
+    limit: Option<u64>,
+    remaining: Option<u64>,
+    /// Time when the rate limit resets, in seconds since the Unix epoch
+    reset: Option<u64>,
+    retry_after: Option<u64>,
+}
+
+impl From<&HeaderMap> for Quota {
+    fn from(headers: &HeaderMap) -> Self {
+        let header = |name: &str| -> Option<u64> {
+            headers.get(name)?.to_str().ok()?.trim().parse::<u64>().ok()
+        };
+        Quota {
+            limit: header("x-ratelimit-limit"),
+            remaining: header("x-ratelimit-remaining"),
+            reset: header("x-ratelimit-reset"),
+            retry_after: header("retry-after"),
+        }
     }
+}
+
+impl Quota {
+    /// Time to wait before a new request can be made, if a rate limit has been exceeded
+    fn wait(&self) -> Option<Duration> {
+        match (self.retry_after, self.remaining, self.reset) {
+            (Some(seconds), _, _) => Some(Duration::from_secs(seconds)),
+            (None, Some(0), Some(reset)) => {
+                Some(Duration::from_secs(reset.saturating_sub(now()) + 1))
+            }
+            _ => None,
+        }
+    }
+
+    fn log(&self) {
+        if let (Some(limit), Some(remaining), Some(reset)) =
+            (self.limit, self.remaining, self.reset)
+        {
+            log::info!(
+                "GitHub API: {}/{} of rate limit remaining, resets in {}s",
+                remaining,
+                limit,
+                reset.saturating_sub(now())
+            );
+        }
+    }
+}
+
+/// Classic tokens and OAuth tokens has their scopes listed in the X-OAuth-Scopes header,
+/// while fine-grained tokens does not
+fn record_scopes(headers: &HeaderMap) {
+    if let Some(scopes) = headers.get("x-oauth-scopes").and_then(|s| s.to_str().ok()) {
+        let scopes: Vec<String> = scopes
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        log::debug!("Token scopes: {:?}", scopes);
+        *SCOPES.write().unwrap() = Some(scopes);
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub(crate) mod v4 {
+    use super::{ApiError, Quota, CLIENT, SECONDARY_BACKOFF, USER_AGENT};
+    use crate::auth::Access;
 
     pub async fn request<T: serde::de::DeserializeOwned>(
         token: &str,
@@ -22,20 +138,172 @@ pub(crate) mod v4 {
     ) -> Result<T, ApiError> {
         log::debug!("{}", query.variables);
 
+        // Mutations may have had an effect even if the response says otherwise, so only queries
+        // are safe to retry
+        let retry: bool = !query.query.trim_start().starts_with("mutation");
+
+        // A mutation is always preceded by a query, so the scopes of the token should be known
+        if let (false, Some(granted)) = (retry, super::scopes()) {
+            if let Some(scope) = crate::auth::missing_scope(&granted, Access::Write) {
+                log::error!("The token needs the scope '{}' for {}", scope, query.operation_name);
+                return Err(ApiError::MissingScope(scope.to_string()));
+            }
+        }
+
+        let body: serde_json::Value = super::retry(retry, || send(token, &query)).await?;
+        serde_json::from_value(body).map_err(|e| ApiError::InvalidResponse(e.to_string()))
+    }
+
+    async fn send(
+        token: &str,
+        query: &crate::search::GraphQLQuery,
+    ) -> Result<serde_json::Value, ApiError> {
         let request: reqwest::Request = CLIENT
-            .post(GITHUB_API_V4_URL)
+            .post(super::graphql_url())
+            .timeout(super::timeout())
             .header("User-Agent", USER_AGENT)
             .bearer_auth(token)
-            .json(&query)
+            .json(query)
             .build()
             .expect("Failed to build query");
 
         let response: reqwest::Response = CLIENT.execute(request).await?;
         let status_code: u16 = response.status().as_u16();
+        let quota = Quota::from(response.headers());
+        quota.log();
+        super::record_scopes(response.headers());
+
         match status_code {
             200 => {
                 log::debug!("GitHub API: {}", status_code);
-                Ok(response.json().await?)
+                let body: serde_json::Value = response.json().await?;
+                log_cost(&body);
+                match rate_limited(&body) {
+                    true => Err(ApiError::RateLimited(quota.wait().unwrap_or(SECONDARY_BACKOFF))),
+                    false => {
+                        log_errors(&body);
+                        Ok(body)
+                    }
+                }
+            }
+            403 | 429 => {
+                let error: String = response.text().await?;
+                match quota.wait() {
+                    Some(wait) => Err(ApiError::RateLimited(wait)),
+                    None if status_code == 429 || error.contains("rate limit") => {
+                        Err(ApiError::RateLimited(SECONDARY_BACKOFF))
+                    }
+                    None => {
+                        log::error!("GitHub API: {} - {}", status_code, error);
+                        Err(ApiError::Response(status_code))
+                    }
+                }
+            }
+            _ => {
+                let error: String = response.text().await?;
+                log::error!("GitHub API: {} - {}", status_code, error);
+                Err(ApiError::Response(status_code))
+            }
+        }
+    }
+
+    /// Log the cost of a query, for queries that includes `rateLimit { cost remaining resetAt }`
+    fn log_cost(body: &serde_json::Value) {
+        if let Some(rate_limit) = body.pointer("/data/rateLimit") {
+            log::info!(
+                "GitHub API: query cost {}, {} remaining until {}",
+                rate_limit["cost"],
+                rate_limit["remaining"],
+                rate_limit["resetAt"].as_str().unwrap_or("unknown")
+            );
+        }
+    }
+
+    /// The primary rate limit of the GraphQL API may be signaled with an error in an otherwise
+    /// successful response
+    fn rate_limited(body: &serde_json::Value) -> bool {
+        match body.get("errors").and_then(|e| e.as_array()) {
+            Some(errors) => errors
+                .iter()
+                .any(|e| e.get("type").and_then(|t| t.as_str()) == Some("RATE_LIMITED")),
+            None => false,
+        }
+    }
+
+    /// GraphQL errors may be returned together with a (partial) response, such as when a
+    /// repository could not be found, so errors are logged but not considered fatal here. Errors
+    /// are only logged as warnings if there still was a result for each field in the response.
+    fn log_errors(body: &serde_json::Value) {
+        let partial: bool = match body.get("data").and_then(|d| d.as_object()) {
+            Some(data) => data.values().all(|v| !v.is_null()),
+            None => false,
+        };
+        if let Some(errors) = body.get("errors").and_then(|e| e.as_array()) {
+            errors
+                .iter()
+                .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+                .for_each(|msg| match partial {
+                    true => log::warn!("GitHub API: {}", msg),
+                    false => log::error!("GitHub API: {}", msg),
+                });
+        }
+    }
+}
+
+pub(crate) mod v3 {
+    use super::{ApiError, Quota, CLIENT, SECONDARY_BACKOFF, USER_AGENT};
+    use reqwest::Method;
+
+    /// Send a GET request to `path` of the REST API, which is retried if it is rate limited
+    pub async fn get<T: serde::de::DeserializeOwned>(
+        token: &str,
+        path: &str,
+    ) -> Result<T, ApiError> {
+        let body: String = super::retry(true, || send(token, Method::GET, path)).await?;
+        serde_json::from_str(&body).map_err(|e| ApiError::InvalidResponse(e.to_string()))
+    }
+
+    /// Send a request that changes something, such as marking a notification as read. These are
+    /// never retried, and any content of the response is ignored.
+    pub async fn update(token: &str, method: Method, path: &str) -> Result<(), ApiError> {
+        send(token, method, path).await.map(|_| ())
+    }
+
+    async fn send(token: &str, method: Method, path: &str) -> Result<String, ApiError> {
+        let url: String = format!("{}{}", super::rest_url(), path);
+        log::debug!("GitHub API: {} {}", method, url);
+        let request: reqwest::Request = CLIENT
+            .request(method, &url)
+            .timeout(super::timeout())
+            .header("User-Agent", USER_AGENT)
+            .header("Accept", "application/vnd.github+json")
+            .bearer_auth(token)
+            .build()
+            .expect("Failed to build request");
+
+        let response: reqwest::Response = CLIENT.execute(request).await?;
+        let status_code: u16 = response.status().as_u16();
+        let quota = Quota::from(response.headers());
+        quota.log();
+        super::record_scopes(response.headers());
+
+        match status_code {
+            200..=299 | 304 => {
+                log::debug!("GitHub API: {}", status_code);
+                Ok(response.text().await?)
+            }
+            403 | 429 => {
+                let error: String = response.text().await?;
+                match quota.wait() {
+                    Some(wait) => Err(ApiError::RateLimited(wait)),
+                    None if status_code == 429 || error.contains("rate limit") => {
+                        Err(ApiError::RateLimited(SECONDARY_BACKOFF))
+                    }
+                    None => {
+                        log::error!("GitHub API: {} - {}", status_code, error);
+                        Err(ApiError::Response(status_code))
+                    }
+                }
             }
             _ => {
                 let error: String = response.text().await?;
@@ -46,6 +314,125 @@ pub(crate) mod v4 {
     }
 }
 
+/// Host of a GitHub instance, which is either github.com or a GitHub Enterprise Server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Host(String);
+
+const GITHUB_HOST: &str = "github.com";
+
+/// Default timeout for a request, including reading the whole response
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
+
+lazy_static! {
+    static ref HOST: RwLock<Host> = RwLock::new(Host::default());
+    static ref API_URL: RwLock<Option<String>> = RwLock::new(None);
+    static ref TIMEOUT: RwLock<Duration> = RwLock::new(DEFAULT_TIMEOUT);
+    static ref SCOPES: RwLock<Option<Vec<String>>> = RwLock::new(None);
+}
+
+/// Set the host that all requests to the API are sent to
+pub fn set_host(host: Host) {
+    log::debug!("Using GitHub host {}", host);
+    *HOST.write().unwrap() = host;
+}
+
+/// The host that all requests to the API are sent to
+pub fn host() -> Host {
+    HOST.read().unwrap().clone()
+}
+
+/// Send all requests to the API at `url` rather than the API of the host, such as when GitHub is
+/// accessed through a proxy
+pub fn set_api_url(url: Option<String>) {
+    if let Some(url) = &url {
+        log::debug!("Using GitHub API at {}", url);
+    }
+    *API_URL.write().unwrap() = url.map(|url| url.trim_end_matches('/').to_string());
+}
+
+pub fn graphql_url() -> String {
+    match API_URL.read().unwrap().as_ref() {
+        Some(url) => format!("{}/graphql", url),
+        None => host().graphql_url(),
+    }
+}
+
+fn rest_url() -> String {
+    match API_URL.read().unwrap().as_ref() {
+        Some(url) => url.clone(),
+        None => host().rest_url(),
+    }
+}
+
+pub fn set_timeout(timeout: Duration) {
+    *TIMEOUT.write().unwrap() = timeout;
+}
+
+fn timeout() -> Duration {
+    *TIMEOUT.read().unwrap()
+}
+
+/// Scopes of the token, as given by the latest response from the API. This is only known for
+/// classic tokens and OAuth tokens.
+pub fn scopes() -> Option<Vec<String>> {
+    SCOPES.read().unwrap().clone()
+}
+
+impl Host {
+    pub fn is_github(&self) -> bool {
+        self.0 == GITHUB_HOST
+    }
+
+    /// URL of the web interface, which is also where OAuth apps are authorized
+    pub fn web_url(&self) -> String {
+        format!("https://{}", self.0)
+    }
+
+    pub fn graphql_url(&self) -> String {
+        match self.is_github() {
+            true => String::from("https://api.github.com/graphql"),
+            false => format!("https://{}/api/graphql", self.0),
+        }
+    }
+
+    /// Base URL of the REST API
+    pub fn rest_url(&self) -> String {
+        match self.is_github() {
+            true => String::from("https://api.github.com"),
+            false => format!("https://{}/api/v3", self.0),
+        }
+    }
+}
+
+impl Default for Host {
+    fn default() -> Self {
+        Host(String::from(GITHUB_HOST))
+    }
+}
+
+impl FromStr for Host {
+    type Err = String;
+
+    /// Parse a host name, such as `ghe.corp.example`. A scheme and a path, as in
+    /// `https://ghe.corp.example/`, is also accepted but ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let host: &str = s.trim();
+        let host: &str = host.split_once("://").map(|(_, host)| host).unwrap_or(host);
+        let host: &str = host.split('/').next().unwrap_or_default();
+        match host {
+            "" => Err(format!("Invalid host '{}'", s)),
+            "api.github.com" => Ok(Host::default()),
+            host => Ok(Host(host.to_lowercase())),
+        }
+    }
+}
+
+impl fmt::Display for Host {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl From<reqwest::Error> for ApiError {
     fn from(e: reqwest::Error) -> Self {
         match e.status() {
@@ -59,4 +446,10 @@ impl From<reqwest::Error> for ApiError {
 pub enum ApiError {
     NoResponse(String),
     Response(u16),
+    InvalidResponse(String),
+    NotCached,
+    /// Rate limit was exceeded, and will reset after the given duration
+    RateLimited(std::time::Duration),
+    /// The token is missing a scope that is needed for the operation
+    MissingScope(String),
 }
@@ -0,0 +1,413 @@
+use crate::{api::Host, settings::config_dir, AppErr};
+use std::{
+    fmt,
+    io::{IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// Where a token was found, in the order that sources are tried
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// Given with `--token` or environment variable `GITHUB_TOKEN`
+    Argument,
+    /// The Secret Service of the desktop (such as GNOME Keyring), through `secret-tool`
+    Keyring,
+    /// A file only readable by the current user, for when there is no Secret Service
+    File,
+    /// The configuration of GitHub's own CLI, `gh`
+    GhCli,
+    /// Any credential helper configured for Git
+    GitCredential,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source: &str = match self {
+            Source::Argument => "--token or GITHUB_TOKEN",
+            Source::Keyring => "system keyring",
+            Source::File => "token file",
+            Source::GhCli => "gh CLI",
+            Source::GitCredential => "git credential helper",
+        };
+        write!(f, "{}", source)
+    }
+}
+
+/// Kind of access that an operation needs
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+impl Access {
+    /// Scopes of classic tokens and OAuth tokens, of which at least one is needed for this access.
+    /// Fine-grained tokens have permissions rather than scopes, which can not be checked.
+    fn scopes(&self) -> &'static [&'static str] {
+        match self {
+            Access::Read => &["read:org", "write:org", "admin:org"],
+            Access::Write => &["repo", "public_repo"],
+        }
+    }
+}
+
+/// Scope needed for `access` that is not among the `granted` scopes, if any
+pub fn missing_scope(granted: &[String], access: Access) -> Option<&'static str> {
+    let scopes: &[&str] = access.scopes();
+    match scopes.iter().any(|scope| granted.iter().any(|g| g == scope)) {
+        true => None,
+        false => scopes.first().copied(),
+    }
+}
+
+/// Sources of tokens other than the command line, in the order they are tried
+const SOURCES: [Source; 4] = [Source::Keyring, Source::File, Source::GhCli, Source::GitCredential];
+
+/// Find a token for `host` from any source other than the command line, stored tokens first
+pub fn token(host: &Host) -> Option<(String, Source)> {
+    first_token(host, |source| source.read(host))
+}
+
+/// The first non-empty token of `SOURCES`, as read by `read`
+fn first_token<F>(host: &Host, read: F) -> Option<(String, Source)>
+where
+    F: Fn(Source) -> Option<String>,
+{
+    SOURCES.iter().find_map(|source| match read(*source) {
+        Some(token) if !token.trim().is_empty() => {
+            log::debug!("Using token for {} from {}", host, source);
+            Some((token.trim().to_string(), *source))
+        }
+        _ => None,
+    })
+}
+
+impl Source {
+    fn read(&self, host: &Host) -> Option<String> {
+        match self {
+            Source::Argument => None,
+            Source::Keyring => keyring::read(host),
+            Source::File => file::read(host),
+            Source::GhCli => gh_token(host),
+            Source::GitCredential => git_credential(host),
+        }
+    }
+}
+
+/// Read a token to log in with, from stdin if it is not a terminal (or `from_stdin` is set),
+/// otherwise by prompting for it without echoing it
+pub fn read_token(host: &Host, from_stdin: bool) -> Result<String, AppErr> {
+    let token: String = match from_stdin || !std::io::stdin().is_terminal() {
+        true => {
+            let mut token = String::new();
+            std::io::stdin().read_to_string(&mut token)?;
+            token
+        }
+        false => rpassword::prompt_password(format!("Paste a token for {}: ", host))?,
+    };
+    match token.trim() {
+        "" => Err(AppErr::InvalidInput(String::from("No token given"))),
+        token => Ok(token.to_string()),
+    }
+}
+
+/// Store a token for `host`, in the system keyring if possible, otherwise in a file
+pub fn store(host: &Host, token: &str) -> Result<Source, AppErr> {
+    match keyring::store(host, token) {
+        Ok(_) => {
+            // Make sure an old token in the file does not linger
+            file::delete(host)?;
+            Ok(Source::Keyring)
+        }
+        Err(e) => {
+            log::info!("Unable to use system keyring ({}), storing token in file", e);
+            file::store(host, token)?;
+            Ok(Source::File)
+        }
+    }
+}
+
+/// Delete any token stored for `host`, returns false if there was no token to delete
+pub fn delete(host: &Host) -> Result<bool, AppErr> {
+    let keyring: bool = keyring::delete(host);
+    let file: bool = file::delete(host)?;
+    Ok(keyring || file)
+}
+
+mod keyring {
+    use super::*;
+
+    const SERVICE: &str = "giss";
+
+    pub fn read(host: &Host) -> Option<String> {
+        let output = Command::new("secret-tool")
+            .args(["lookup", "service", SERVICE, "host", &host.to_string()])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        match output.status.success() {
+            true => String::from_utf8(output.stdout).ok(),
+            false => None,
+        }
+    }
+
+    pub fn store(host: &Host, token: &str) -> Result<(), String> {
+        let label: String = format!("giss token for {}", host);
+        let mut child = Command::new("secret-tool")
+            .args(["store", "--label", &label, "service", SERVICE, "host", &host.to_string()])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(token.as_bytes()).map_err(|e| e.to_string())?;
+        }
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        match output.status.success() {
+            true => Ok(()),
+            false => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        }
+    }
+
+    pub fn delete(host: &Host) -> bool {
+        let existed: bool = read(host).is_some();
+        let cleared: bool = Command::new("secret-tool")
+            .args(["clear", "service", SERVICE, "host", &host.to_string()])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+        existed && cleared
+    }
+}
+
+mod file {
+    use super::*;
+
+    fn path(host: &Host) -> PathBuf {
+        config_dir().join("tokens").join(host.to_string())
+    }
+
+    /// Read the token, unless the file can be read by other users than the current user, in
+    /// which case it is ignored, just like SSH ignores private keys that are readable by others
+    pub fn read(host: &Host) -> Option<String> {
+        let path: PathBuf = path(host);
+        let file = std::fs::File::open(&path).ok()?;
+        if !is_private(&file) {
+            log::warn!("Ignoring token in {:?}, since other users can read it", path);
+            log::warn!("Run 'giss auth login' again, or restrict the file with 'chmod 600'");
+            return None;
+        }
+        std::io::read_to_string(file).ok()
+    }
+
+    pub fn store(host: &Host, token: &str) -> Result<(), AppErr> {
+        let path: PathBuf = path(host);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file: std::fs::File = create(&path).map_err(|e| {
+            log::error!("Unable to write token to {:?}: {}", path, e);
+            AppErr::TokenWriteError
+        })?;
+        file.write_all(token.as_bytes()).map_err(|_| AppErr::TokenWriteError)
+    }
+
+    pub fn delete(host: &Host) -> Result<bool, AppErr> {
+        match std::fs::remove_file(path(host)) {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Create the file so it is only readable and writable by the current user, also when it
+    /// already exists with other permissions
+    #[cfg(unix)]
+    fn create(path: &Path) -> Result<std::fs::File, std::io::Error> {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        Ok(file)
+    }
+
+    /// Permissions can only be restricted on Unix, elsewhere the file is only protected by the
+    /// access control list of the user's configuration directory, which is inherited by the file
+    #[cfg(not(unix))]
+    fn create(path: &Path) -> Result<std::fs::File, std::io::Error> {
+        log::warn!("Token is stored in {:?}, which is not encrypted", path);
+        std::fs::File::create(path)
+    }
+
+    #[cfg(unix)]
+    pub(super) fn is_private(file: &std::fs::File) -> bool {
+        use std::os::unix::fs::PermissionsExt;
+        match file.metadata() {
+            Ok(meta) => meta.permissions().mode() & 0o077 == 0,
+            Err(_) => false,
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub(super) fn is_private(_file: &std::fs::File) -> bool {
+        true
+    }
+}
+
+/// Read the token for `host` from the `hosts.yml` of GitHub's CLI. Recent versions of gh keep
+/// the token in the system keyring instead, in which case there is no token in the file.
+fn gh_token(host: &Host) -> Option<String> {
+    let config_dir = std::env::var_os("GH_CONFIG_DIR");
+    let dir: PathBuf = match (config_dir, std::env::var_os("XDG_CONFIG_HOME")) {
+        (Some(dir), _) => PathBuf::from(dir),
+        (None, Some(dir)) => PathBuf::from(dir).join("gh"),
+        (None, None) => dirs_next::home_dir()?.join(".config").join("gh"),
+    };
+    let content: String = std::fs::read_to_string(dir.join("hosts.yml")).ok()?;
+    hosts_yml_token(&content, &host.to_string())
+}
+
+/// Find `oauth_token` for a host in a `hosts.yml` file, which has the format
+///
+/// ```yaml
+/// github.com:
+///     user: octocat
+///     oauth_token: gho_abc123
+/// ```
+///
+/// Only keys directly under the host are considered, not those of nested maps such as `users`.
+fn hosts_yml_token(content: &str, host: &str) -> Option<String> {
+    let mut in_host: bool = false;
+    let mut indent: Option<usize> = None;
+    for line in content.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let depth: usize = line.len() - line.trim_start().len();
+        match line.starts_with(char::is_whitespace) {
+            false => {
+                in_host = line.trim_end().trim_end_matches(':').trim_matches('"') == host;
+                indent = None;
+            }
+            true if in_host && *indent.get_or_insert(depth) == depth => {
+                if let Some((key, value)) = line.trim().split_once(':') {
+                    if key.trim() == "oauth_token" {
+                        let token: &str = value.trim().trim_matches('"').trim_matches('\'');
+                        return Some(token.to_string());
+                    }
+                }
+            }
+            true => {}
+        }
+    }
+    None
+}
+
+/// Ask Git's credential helpers for a password for `host`, without ever prompting the user
+fn git_credential(host: &Host) -> Option<String> {
+    let mut child = Command::new("git")
+        .args(["credential", "fill"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_ASKPASS", "")
+        .env("SSH_ASKPASS", "")
+        .env("GCM_INTERACTIVE", "never")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    let request: String = format!("protocol=https\nhost={}\n\n", host);
+    child.stdin.take()?.write_all(request.as_bytes()).ok()?;
+
+    let mut output = String::new();
+    child.stdout.take()?.read_to_string(&mut output).ok()?;
+    match child.wait().ok()?.success() {
+        true => output
+            .lines()
+            .find_map(|line| line.strip_prefix("password="))
+            .map(|password| password.to_string()),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOSTS_YML: &str = r#"
+# Written by gh
+github.com:
+    user: octocat
+    oauth_token: gho_abc123
+    git_protocol: ssh
+"ghe.corp.example":
+    users:
+        octocat:
+            oauth_token: not_this_one
+    oauth_token: 'gho_def456'
+keyring.example:
+    user: octocat
+"#;
+
+    #[test]
+    fn token_of_host_in_hosts_yml() {
+        assert_eq!(Some(String::from("gho_abc123")), hosts_yml_token(HOSTS_YML, "github.com"));
+        let token: Option<String> = hosts_yml_token(HOSTS_YML, "ghe.corp.example");
+        assert_eq!(Some(String::from("gho_def456")), token);
+    }
+
+    #[test]
+    fn no_token_of_other_host_in_hosts_yml() {
+        assert_eq!(None, hosts_yml_token(HOSTS_YML, "keyring.example"));
+        assert_eq!(None, hosts_yml_token(HOSTS_YML, "github.co"));
+        assert_eq!(None, hosts_yml_token("", "github.com"));
+    }
+
+    #[test]
+    fn sources_are_tried_in_order() {
+        let host = Host::default();
+        let all = |_: Source| Some(String::from("token"));
+        assert_eq!(Some((String::from("token"), Source::Keyring)), first_token(&host, all));
+
+        let without_keyring = |source: Source| match source {
+            Source::Keyring => None,
+            source => Some(format!("{:?}", source)),
+        };
+        let expected = Some((String::from("File"), Source::File));
+        assert_eq!(expected, first_token(&host, without_keyring));
+    }
+
+    #[test]
+    fn empty_tokens_are_skipped() {
+        let host = Host::default();
+        let read = |source: Source| match source {
+            Source::GitCredential => Some(String::from(" ghp_xyz\n")),
+            _ => Some(String::from(" \n")),
+        };
+        let expected = Some((String::from("ghp_xyz"), Source::GitCredential));
+        assert_eq!(expected, first_token(&host, read));
+        assert_eq!(None, first_token(&host, |_| None));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn token_file_readable_by_others_is_not_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let file = tempfile::NamedTempFile::new().unwrap();
+        for (mode, private) in [(0o600, true), (0o400, true), (0o640, false), (0o604, false)] {
+            file.as_file().set_permissions(std::fs::Permissions::from_mode(mode)).unwrap();
+            assert_eq!(private, file::is_private(file.as_file()), "{:o}", mode);
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use crate::AppErr;
+use std::process::{Command, Stdio};
+
+/// Open `url` in the default browser of the user
+pub fn open(url: &str) -> Result<(), AppErr> {
+    let (program, args): (&str, Vec<&str>) = if cfg!(target_os = "macos") {
+        ("open", vec![url])
+    } else if cfg!(target_os = "windows") {
+        ("cmd", vec!["/C", "start", "", url])
+    } else {
+        ("xdg-open", vec![url])
+    };
+
+    Command::new(program)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use crate::{api::ApiError, search::GraphQLQuery};
+use lazy_static::lazy_static;
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+use tokio::task::JoinHandle;
+
+/// How cached API responses should be used
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Use a cached response if there is one, but also request a fresh response in the
+    /// background, which will be used on the next invocation (stale-while-revalidate)
+    Default,
+    /// Only use cached responses, without making any requests to the API
+    Offline,
+    /// Ignore any cached response, but save the new response to the cache
+    Refresh,
+}
+
+/// Cached responses which have not been written for this long are removed from the cache
+const MAX_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+
+lazy_static! {
+    /// Requests for revalidating cached responses that are still running in the background
+    static ref PENDING: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+}
+
+/// Send a GraphQL query, with cached responses used according to the given `CacheMode`. Responses
+/// are cached per token and query, so the same query for different users will not share cache.
+pub async fn request<T: DeserializeOwned>(
+    token: &str,
+    query: GraphQLQuery,
+    mode: CacheMode,
+) -> Result<T, ApiError> {
+    let key: String = key(token, &query);
+    log::debug!("Cache key for {}: {}", query.operation_name, key);
+    let cached: Option<serde_json::Value> = match mode {
+        CacheMode::Refresh => None,
+        CacheMode::Default | CacheMode::Offline => read(&key),
+    };
+
+    match (cached, mode) {
+        (Some(body), CacheMode::Offline) => parse(body),
+        (None, CacheMode::Offline) => {
+            let operation: &str = &query.operation_name;
+            log::error!("No cached response for {}, run without --offline first", operation);
+            Err(ApiError::NotCached)
+        }
+        (Some(body), _) => {
+            log::debug!("Using cached response for {} ({})", query.operation_name, key);
+            let token: String = token.to_string();
+            let handle = tokio::spawn(async move {
+                if let Err(e) = fetch::<serde_json::Value>(&token, query, &key).await {
+                    log::warn!("Unable to revalidate cached response: {:?}", e);
+                }
+            });
+            PENDING.lock().unwrap().push(handle);
+            parse(body)
+        }
+        (None, _) => fetch(token, query, &key).await,
+    }
+}
+
+/// Wait for any revalidation of cached responses that is still running, so the cache is
+/// updated before the application exits.
+pub async fn settle() {
+    let pending: Vec<JoinHandle<()>> = PENDING.lock().unwrap().drain(..).collect();
+    for handle in pending {
+        if let Err(e) = handle.await {
+            log::warn!("Revalidation of cache failed: {}", e);
+        }
+    }
+}
+
+async fn fetch<T: DeserializeOwned>(
+    token: &str,
+    query: GraphQLQuery,
+    key: &str,
+) -> Result<T, ApiError> {
+    let body: serde_json::Value = crate::api::v4::request(token, query).await?;
+    let response: T = parse(body.clone())?;
+    if let Err(e) = write(key, &body) {
+        log::warn!("Unable to write response to cache: {}", e);
+    }
+    Ok(response)
+}
+
+fn parse<T: DeserializeOwned>(body: serde_json::Value) -> Result<T, ApiError> {
+    serde_json::from_value(body).map_err(|e| ApiError::InvalidResponse(e.to_string()))
+}
+
+fn key(token: &str, query: &GraphQLQuery) -> String {
+    let mut hasher = Sha256::new();
+    hasher.input(token);
+    hasher.input(crate::api::graphql_url());
+    hasher.input(&query.operation_name);
+    hasher.input(&query.query);
+    hasher.input(query.variables.to_string());
+    format!("{:02x}", hasher.result())
+}
+
+/// Directory of cached responses, which can be moved with the environment variable
+/// `GISS_CACHE_DIR`
+pub fn cache_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("GISS_CACHE_DIR") {
+        return PathBuf::from(dir);
+    }
+    dirs_next::cache_dir()
+        .unwrap_or_else(|| crate::settings::config_dir().join("cache"))
+        .join("giss")
+}
+
+fn read(key: &str) -> Option<serde_json::Value> {
+    let path: PathBuf = cache_dir().join(key);
+    if is_expired(&path, SystemTime::now()) {
+        return None;
+    }
+    let content: String = std::fs::read_to_string(&path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(body) => Some(body),
+        Err(e) => {
+            log::warn!("Ignoring invalid cache file {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Write to a temporary file first, so a cached response is never read while only partially
+/// written. Cached responses may contain private data, so they are only readable by the user.
+fn write(key: &str, body: &serde_json::Value) -> Result<(), std::io::Error> {
+    let dir: PathBuf = cache_dir();
+    std::fs::create_dir_all(&dir)?;
+    let file = tempfile::NamedTempFile::new_in(&dir)?;
+    restrict(file.as_file())?;
+    serde_json::to_writer(&file, body)?;
+    file.persist(dir.join(key))?;
+    prune(&dir, SystemTime::now());
+    Ok(())
+}
+
+#[cfg(unix)]
+fn restrict(file: &std::fs::File) -> Result<(), std::io::Error> {
+    use std::os::unix::fs::PermissionsExt;
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))
+}
+
+#[cfg(not(unix))]
+fn restrict(_file: &std::fs::File) -> Result<(), std::io::Error> {
+    Ok(())
+}
+
+fn is_expired(path: &Path, now: SystemTime) -> bool {
+    match std::fs::metadata(path).and_then(|meta| meta.modified()) {
+        Ok(modified) => now.duration_since(modified).is_ok_and(|age| age > MAX_AGE),
+        Err(_) => false,
+    }
+}
+
+/// Remove cached responses which are older than `MAX_AGE`
+fn prune(dir: &Path, now: SystemTime) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::warn!("Unable to prune cache {:?}: {}", dir, e);
+            return;
+        }
+    };
+    for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        if path.is_file() && is_expired(&path, now) {
+            log::debug!("Removing expired cache file {:?}", path);
+            if let Err(e) = std::fs::remove_file(&path) {
+                log::warn!("Unable to remove expired cache file {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prune_removes_only_expired_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let fresh: PathBuf = dir.path().join("fresh");
+        let old: PathBuf = dir.path().join("old");
+        std::fs::write(&fresh, "{}").unwrap();
+        std::fs::write(&old, "{}").unwrap();
+        let modified: SystemTime = SystemTime::now() - MAX_AGE - Duration::from_secs(60);
+        std::fs::File::options().write(true).open(&old).unwrap().set_modified(modified).unwrap();
+
+        prune(dir.path(), SystemTime::now());
+
+        assert!(fresh.exists());
+        assert!(!old.exists());
+    }
+
+    #[test]
+    fn missing_file_is_not_expired() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!is_expired(&dir.path().join("missing"), SystemTime::now()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restricted_file_is_only_readable_by_user() {
+        use std::os::unix::fs::PermissionsExt;
+        let file = tempfile::NamedTempFile::new().unwrap();
+        file.as_file().set_permissions(std::fs::Permissions::from_mode(0o644)).unwrap();
+        restrict(file.as_file()).unwrap();
+        let mode: u32 = file.as_file().metadata().unwrap().permissions().mode();
+        assert_eq!(0o600, mode & 0o777);
+    }
+}
@@ -1,20 +1,473 @@
-use std::str::FromStr;
+use std::{cell::OnceCell, str::FromStr, time::Duration};
 
 use crate::{
-    args::read_repo_from_file,
+    api::Host,
+    auth::{self, Source},
+    cache::CacheMode,
+    git,
+    github_resources::ghrs::StateReason,
+    inbox::{self, InboxConfig},
     list::StateFilter,
+    notify::NotifyConfig,
+    output::OutputFormat,
     project::Project,
+    qualifier::{DateFilter, Missing, Qualifiers, Review, Status, When},
+    saved,
+    settings::{Profile, Settings},
     sort::{Order, Property, Sorting},
-    target::Target,
+    target::{IssueRef, Target},
     user::Username,
+    watch::Interval,
     AppErr,
 };
-use structopt::StructOpt;
+use structopt::{clap::AppSettings, StructOpt};
 use termcolor::ColorChoice;
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "giss", author, about)]
 pub struct Config {
+    #[structopt(flatten)]
+    global: GlobalArgs,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+
+    // Arguments for listing issues and pull requests, which is done when no subcommand is given
+    #[structopt(flatten)]
+    list: ListArgs,
+
+    #[structopt(skip)]
+    settings: Profile,
+
+    #[structopt(skip)]
+    token: OnceCell<Option<(String, Source)>>,
+}
+
+// Options that are shared by all subcommands
+#[derive(StructOpt, Debug)]
+pub struct GlobalArgs {
+    /// GitHub API token
+    ///
+    /// API token that will be used when authenticating towards GitHub's API
+    #[structopt(short, long, env = "GITHUB_TOKEN", hide_env_values = true, global = true)]
+    token: Option<String>,
+
+    /// GitHub host
+    ///
+    /// Host of the GitHub instance to use, such as "ghe.corp.example" for GitHub Enterprise
+    /// Server. Defaults to the host of the Git remote of the current repository, or github.com if
+    /// not in a repository.
+    #[structopt(long, env = "GISS_HOST", global = true)]
+    host: Option<Host>,
+
+    /// URL of GitHub's API
+    ///
+    /// Base URL of GitHub's API, such as "https://proxy.corp.example/github", when it is not at
+    /// the default location for the host. GraphQL requests are sent to <url>/graphql.
+    #[structopt(long, env = "GISS_API_URL", global = true)]
+    api_url: Option<String>,
+
+    /// Timeout for requests
+    ///
+    /// Timeout for each request to GitHub's API, such as "30s" or "1m". [default: 15s]
+    #[structopt(long, env = "GISS_TIMEOUT", global = true)]
+    timeout: Option<Interval>,
+
+    /// Use profile
+    ///
+    /// Use settings from a named profile in the configuration file (~/.config/giss/config.toml),
+    /// for example "oncall" for a profile declared as [profile.oncall]. Any option given on the
+    /// command line overrides the settings from the configuration file.
+    #[structopt(long, env = "GISS_PROFILE", global = true)]
+    profile: Option<String>,
+
+    /// Set use of colors
+    ///
+    /// Enable or disable output with colors. By default, the application will
+    /// try to figure out if colors are supported by the terminal in the current context, and use it
+    /// if possible.
+    /// Possible values are "on", "true", "off", "false", "auto". [default: auto]
+    #[structopt(long = "colors", global = true)]
+    colors: Option<Flag>,
+
+    /// Set verbosity level, 0 - 5
+    ///
+    /// Set the verbosity level, from 0 (least amount of output) to 5 (most verbose). Note that
+    /// logging level configured via RUST_LOG overrides this setting. [default: 1]
+    #[structopt(short, long, global = true)]
+    verbosity: Option<Verbosity>,
+
+    /// Prind debug information
+    ///
+    /// Print debug information about current build for binary, useful for when an issue is
+    /// encountered and reported
+    #[structopt(short = "D", long, global = true)]
+    debug: bool,
+
+    /// Only use cached results
+    ///
+    /// Do not make any requests to GitHub's API, and only show results that were cached by a
+    /// previous invocation of the same query. By default, cached results are shown immediately,
+    /// while fresh results are fetched in the background and cached for the next invocation.
+    #[structopt(long, global = true, conflicts_with = "refresh")]
+    offline: bool,
+
+    /// Ignore cached results
+    ///
+    /// Always wait for fresh results from GitHub's API, rather than showing cached results
+    #[structopt(long, global = true)]
+    refresh: bool,
+}
+
+#[derive(StructOpt, Debug)]
+pub enum Command {
+    /// List issues and pull requests
+    ///
+    /// List issues, pull requests and review requests. This is the default subcommand, which is
+    /// used when no other subcommand is given.
+    List(Box<ListArgs>),
+
+    /// Show an issue or pull request
+    ///
+    /// Show a single issue or pull request in detail, including its body and comments. For pull
+    /// requests, the status of reviews and checks is shown as well.
+    Show(ShowArgs),
+
+    /// Create an issue
+    ///
+    /// Create a new issue, by writing it in the editor given by $VISUAL or $EDITOR. The title is
+    /// written on the first line and the body below it, while labels, assignees and milestone are
+    /// set in the front matter. If the repository has any issue templates (in
+    /// .github/ISSUE_TEMPLATE), one of them can be used as a starting point. The editor is not
+    /// opened if a title is given as an argument.
+    Create(CreateArgs),
+
+    /// Comment on an issue or pull request
+    ///
+    /// Add a comment to an issue or pull request. The comment is taken from --message if given,
+    /// or else from stdin if it is not a terminal, or else written in the editor given by
+    /// $VISUAL or $EDITOR.
+    Comment(CommentArgs),
+
+    /// Close an issue or pull request
+    Close(StateArgs),
+
+    /// Reopen a closed issue or pull request
+    Reopen(StateArgs),
+
+    /// Assign users to an issue or pull request
+    ///
+    /// Assign one or several users to an issue or pull request, or remove them with --remove.
+    /// When no user is given, the user of the token is used.
+    Assign(AssignArgs),
+
+    /// Add labels to an issue or pull request
+    ///
+    /// Add one or several labels to an issue or pull request, or remove them with --remove
+    Label(LabelArgs),
+
+    /// Inspect configuration
+    ///
+    /// Inspect the configuration file and the settings resolved from it
+    Config(ConfigCommand),
+
+    /// Notify about review requests, assignments and mentions
+    ///
+    /// Poll for new review requests, assignments and mentions of the user, and send a desktop
+    /// notification (with notify-send) for each one. Items are only notified once, and nothing is
+    /// notified on the very first poll. Items that have been seen are saved in
+    /// ~/.config/giss/notify.
+    Notify(NotifyArgs),
+
+    /// Save a search
+    ///
+    /// Save the arguments after the name under that name, so the same search can be run later
+    /// with `giss run <name>`. The arguments are the same as for `giss list`, such as
+    /// `giss save triage -i --no assignee my-org`. Saved searches are kept in
+    /// ~/.config/giss/saved.toml.
+    Save(SaveArgs),
+
+    /// Run a saved search
+    ///
+    /// Run a search that was saved with `giss save`. Any arguments after the name are added to
+    /// the arguments of the saved search.
+    Run(RunArgs),
+
+    /// Manage saved searches
+    Saved(SavedCommand),
+
+    /// List and manage notifications
+    ///
+    /// List unread notifications on GitHub, with the reason for each notification, its
+    /// repository, type and title. Notifications can be marked as read, or unsubscribed from, by
+    /// the id given in the list.
+    Inbox(InboxArgs),
+
+    /// Manage authentication
+    ///
+    /// Store a token for the GitHub host, so it does not have to be given with --token or
+    /// GITHUB_TOKEN. When no token is given or stored, a token from GitHub's CLI (gh) or from
+    /// Git's credential helpers is used, if there is one.
+    Auth(AuthCommand),
+}
+
+#[derive(StructOpt, Debug)]
+pub struct ShowArgs {
+    /// Issue or pull request to show, for example 'owner/repo#123'. If only the number is
+    /// given, the repository in the current directory will be used.
+    pub issue: IssueRef,
+}
+
+#[derive(StructOpt, Debug)]
+pub struct CreateArgs {
+    /// Repository to create the issue in, for example 'owner/repo'. When no repository is given,
+    /// the repository in the current directory will be used.
+    pub repo: Option<Target>,
+
+    /// Name of issue template to use, either the name given in the template or its file name
+    #[structopt(short = "T", long)]
+    pub template: Option<String>,
+
+    /// Title of the issue
+    #[structopt(long)]
+    pub title: Option<String>,
+
+    /// Body of the issue
+    #[structopt(short, long)]
+    pub body: Option<String>,
+
+    /// Label(s) to add to the issue
+    #[structopt(short, long)]
+    pub labels: Vec<String>,
+
+    /// User(s) to assign to the issue
+    #[structopt(short, long)]
+    pub assignees: Vec<String>,
+
+    /// Milestone, given by its title or number, to add the issue to
+    #[structopt(short, long)]
+    pub milestone: Option<String>,
+}
+
+impl CreateArgs {
+    /// Owner and name of the repository to create the issue in
+    pub fn repository(&self) -> Result<(String, String), AppErr> {
+        let target: Target = match &self.repo {
+            Some(target) => target.clone(),
+            None => Target::current()?,
+        };
+        match target {
+            Target::Repository(owner, name) => Ok((owner, name)),
+            Target::Organization(name) => Err(AppErr::InvalidTarget(name)),
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+pub struct CommentArgs {
+    /// Issue or pull request to comment on, for example 'owner/repo#123'. If only the number is
+    /// given, the repository in the current directory will be used.
+    pub issue: IssueRef,
+
+    /// Comment to add, or "-" to read it from stdin
+    #[structopt(short, long)]
+    pub message: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+pub struct StateArgs {
+    /// Issue or pull request, for example 'owner/repo#123'. If only the number is given, the
+    /// repository in the current directory will be used.
+    pub issue: IssueRef,
+
+    /// Comment to add before the state is changed, or "-" to read it from stdin
+    #[structopt(short, long)]
+    pub message: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+pub struct AssignArgs {
+    /// Issue or pull request, for example 'owner/repo#123'. If only the number is given, the
+    /// repository in the current directory will be used.
+    pub issue: IssueRef,
+
+    /// User(s) to assign. Will default to the user of the token.
+    pub users: Vec<String>,
+
+    /// Remove the user(s) as assignees instead
+    #[structopt(short, long)]
+    pub remove: bool,
+}
+
+#[derive(StructOpt, Debug)]
+pub struct LabelArgs {
+    /// Issue or pull request, for example 'owner/repo#123'. If only the number is given, the
+    /// repository in the current directory will be used.
+    pub issue: IssueRef,
+
+    /// Label(s) to add
+    #[structopt(required = true)]
+    pub labels: Vec<String>,
+
+    /// Remove the label(s) instead
+    #[structopt(short, long)]
+    pub remove: bool,
+}
+
+#[derive(StructOpt, Debug)]
+pub enum ConfigCommand {
+    /// Print the path of the configuration file
+    Path,
+
+    /// Print the settings resolved from the configuration file, for the selected profile
+    Show,
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(setting = AppSettings::TrailingVarArg, setting = AppSettings::AllowLeadingHyphen)]
+pub struct SaveArgs {
+    /// Name of the search
+    pub name: String,
+
+    /// Arguments for listing issues and pull requests, as for `giss list`
+    pub args: Vec<String>,
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(setting = AppSettings::TrailingVarArg, setting = AppSettings::AllowLeadingHyphen)]
+pub struct RunArgs {
+    /// Name of the search
+    pub name: String,
+
+    /// Additional arguments for listing issues and pull requests, as for `giss list`
+    pub args: Vec<String>,
+}
+
+#[derive(StructOpt, Debug)]
+pub enum SavedCommand {
+    /// List saved searches
+    List,
+
+    /// Delete a saved search
+    Delete { name: String },
+
+    /// Edit the arguments of a saved search in the editor given by $VISUAL or $EDITOR
+    Edit { name: String },
+}
+
+#[derive(StructOpt, Debug)]
+pub struct NotifyArgs {
+    /// Only notify about items in these organizations or repositories, such as 'org' or
+    /// 'org/repo'. By default, items in any repository are included.
+    target: Vec<Target>,
+
+    /// Interval between polls, such as "30s", "5m" or "1h"
+    #[structopt(short, long, default_value = "5m")]
+    interval: Interval,
+
+    /// Command to run for each notification, instead of a desktop notification
+    ///
+    /// Command to run (with sh -c, or cmd /C on Windows) for each notification, instead of sending
+    /// a desktop notification with notify-send (or osascript on macOS). Windows has no desktop
+    /// notifications, so they are only printed unless a command is given. The details of the
+    /// notification are available in the environment variables GISS_REASON, GISS_REPO,
+    /// GISS_NUMBER, GISS_TITLE and GISS_URL.
+    #[structopt(short, long, env = "GISS_NOTIFY_COMMAND")]
+    command: Option<String>,
+
+    /// Poll once and exit, rather than polling until interrupted
+    #[structopt(long)]
+    once: bool,
+}
+
+#[derive(StructOpt, Debug)]
+pub struct InboxArgs {
+    #[structopt(subcommand)]
+    pub action: Option<InboxCommand>,
+
+    /// Only list notifications with these reasons
+    ///
+    /// Only list notifications with these reasons, such as review_requested, mention or assign.
+    /// Several reasons can be given, separated by commas.
+    #[structopt(short, long, require_delimiter = true)]
+    reason: Vec<inbox::Reason>,
+
+    /// Also list notifications that have been read
+    #[structopt(short, long)]
+    all: bool,
+
+    /// Limit the number of notifications that are listed
+    #[structopt(short = "n", long, default_value = "30")]
+    limit: u32,
+}
+
+#[derive(StructOpt, Debug)]
+pub enum InboxCommand {
+    /// Mark notifications as read
+    Read {
+        /// Id(s) of notifications to mark as read
+        #[structopt(required_unless = "all")]
+        ids: Vec<u64>,
+
+        /// Mark all notifications as read
+        #[structopt(short, long, conflicts_with = "ids")]
+        all: bool,
+    },
+
+    /// Unsubscribe from notifications
+    ///
+    /// Unsubscribe from notifications, so there will be no more notifications about the same
+    /// issue or pull request unless the user is mentioned or participates in it again
+    Unsubscribe {
+        /// Id(s) of notifications to unsubscribe from
+        #[structopt(required = true)]
+        ids: Vec<u64>,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+pub enum AuthCommand {
+    /// Log in by storing a token in the system keyring
+    ///
+    /// Log in by storing a token in the system keyring (Secret Service), or in a file that only
+    /// the current user can read if there is no keyring. The token is read from stdin if it is not
+    /// a terminal, otherwise it is prompted for.
+    Login(LoginArgs),
+
+    /// Log out by deleting the stored token
+    Logout,
+
+    /// Show which user is logged in, and where the token is from
+    Status,
+}
+
+#[derive(StructOpt, Debug)]
+pub struct LoginArgs {
+    /// Read the token from stdin
+    #[structopt(long, conflicts_with = "web")]
+    pub with_token: bool,
+
+    /// Log in with the browser, which requires --client-id
+    ///
+    /// Log in with the browser, by entering a code in GitHub's web interface that authorizes an
+    /// OAuth app to access GitHub for giss. giss does not come with an OAuth app of its own, so
+    /// this requires the client id of an OAuth app with the device flow enabled, which can be
+    /// registered under Settings > Developer settings > OAuth Apps at GitHub. The client id is
+    /// given with --client-id or as oauth_client_id in the configuration file.
+    #[structopt(short, long)]
+    pub web: bool,
+
+    /// Client id of the OAuth app to log in with, required when logging in with --web
+    #[structopt(long, env = "GISS_OAUTH_CLIENT_ID")]
+    client_id: Option<String>,
+
+    /// Scopes to request when logging in with --web
+    #[structopt(long, use_delimiter = true, default_value = "repo,read:org")]
+    pub scopes: Vec<String>,
+}
+
+#[derive(StructOpt, Debug)]
+pub struct ListArgs {
     /// Name of target(s)
     ///
     /// Name of the targets for the action. Can be a combination of one or several repositories,
@@ -23,21 +476,24 @@ pub struct Config {
     /// current directory will be used, if possible.
     target: Vec<Target>,
 
-    /// GitHub API token
-    ///
-    /// API token that will be used when authenticating towards GitHub's API
-    #[structopt(short, long, env = "GITHUB_TOKEN", hide_env_values = true)]
-    token: Option<String>,
-
     /// Assigned only
     ///
     /// Only include issues and pull requests assigned to user
     #[structopt(short, long)]
     assigned: bool,
 
+    /// Include issues and pull requests regardless of assignee, even if the configuration file
+    /// sets `assigned = true`
+    #[structopt(long, conflicts_with = "assigned")]
+    no_assigned: bool,
+
     /// Limit the number of issues or pull requests to list
-    #[structopt(short = "n", long, default_value = "10")]
-    limit: u32,
+    ///
+    /// Results are fetched in pages of at most 100 items until the limit is reached. Note that
+    /// GitHub's search API will never return more than 1000 results for a single query.
+    /// [default: 10]
+    #[structopt(short = "n", long)]
+    limit: Option<u32>,
 
     /// Show open issues or pull requests
     ///
@@ -96,6 +552,21 @@ pub struct Config {
     #[structopt(short = "S", long)]
     search: Option<String>,
 
+    /// Raw search query
+    ///
+    /// Search query in GitHub's search syntax, such as "author:mantono no:milestone", which is
+    /// added as is to the query built from all other options.
+    #[structopt(long)]
+    query: Option<String>,
+
+    /// Explain query
+    ///
+    /// Print the search query and the GraphQL variables of each request that would be sent to
+    /// GitHub's API, without sending them. The user of the token may still be looked up, if it is
+    /// not known since before.
+    #[structopt(long)]
+    explain: bool,
+
     /// Username
     ///
     /// Username to use for the query. Will default to the username for the user of the token.
@@ -108,37 +579,188 @@ pub struct Config {
     #[structopt(short = "L", long)]
     links: bool,
 
-    /// Set use of colors
+    /// Do not show links, even if the configuration file sets `links = true`
+    #[structopt(long, conflicts_with = "links")]
+    no_links: bool,
+
+    /// Output format
     ///
-    /// Enable or disable output with colors. By default, the application will
-    /// try to figure out if colors are supported by the terminal in the current context, and use it
-    /// if possible.
-    /// Possible values are "on", "true", "off", "false", "auto".
-    #[structopt(long = "colors", default_value = "auto")]
-    colors: Flag,
+    /// Format to print issues and pull requests in. Possible values are "text" (default),
+    /// "json" (a single JSON array), "ndjson" (one JSON object per line) and "csv". Any format
+    /// other than "text" is intended to be consumed by other tools, such as jq or spreadsheets.
+    /// [default: text]
+    #[structopt(long)]
+    output: Option<OutputFormat>,
 
-    /// Set verbosity level, 0 - 5
+    /// Interactive mode
     ///
-    /// Set the verbosity level, from 0 (least amount of output) to 5 (most verbose). Note that
-    /// logging level configured via RUST_LOG overrides this setting.
-    #[structopt(short, long, default_value = "1")]
-    verbosity: Verbosity,
+    /// Browse issues and pull requests in an interactive full-screen view, where the list can be
+    /// navigated with the arrow keys (or j/k), filtered with /, refreshed with r and where an
+    /// issue can be shown in detail with enter or opened in the browser with o. Quit with q.
+    #[structopt(short = "I", long)]
+    interactive: bool,
 
-    /// Prind debug information
+    /// Refresh periodically
     ///
-    /// Print debug information about current build for binary, useful for when an issue is
-    /// encountered and reported
-    #[structopt(short = "D", long)]
-    debug: bool,
+    /// Refresh the list at the given interval, such as "30s", "5m" or "1h", by redrawing it in
+    /// place. Issues and pull requests that are new or have been updated since the previous
+    /// refresh are marked with + and * respectively.
+    #[structopt(short = "W", long, conflicts_with = "interactive")]
+    watch: Option<Interval>,
+
+    // Search qualifiers, which are only given as long options
+    #[structopt(flatten)]
+    qualifiers: QualifierArgs,
+}
+
+#[derive(StructOpt, Debug)]
+pub struct QualifierArgs {
+    /// Filter by author
+    #[structopt(long)]
+    author: Option<String>,
+
+    /// Filter by mentioned user
+    #[structopt(long)]
+    mentions: Option<String>,
+
+    /// Filter by user who has commented
+    #[structopt(long)]
+    commenter: Option<String>,
+
+    /// Filter by involved user
+    ///
+    /// Only include issues and pull requests that involve the user in any way, either as
+    /// author, assignee, commenter or by being mentioned.
+    #[structopt(long)]
+    involves: Option<String>,
+
+    /// Filter pull requests by user who has reviewed them
+    #[structopt(long)]
+    reviewed_by: Option<String>,
+
+    /// Filter by milestone
+    #[structopt(long)]
+    milestone: Option<String>,
+
+    /// Filter by missing field
+    ///
+    /// Only include issues and pull requests which has no value for the field(s), which can be
+    /// any of "assignee", "label", "milestone" and "project".
+    #[structopt(long = "no", require_delimiter = true)]
+    missing: Vec<Missing>,
+
+    /// Exclude label
+    ///
+    /// Exclude issues, pull requests or review requests which has any of the given label(s).
+    #[structopt(long, number_of_values = 1)]
+    exclude_label: Vec<String>,
+
+    /// Filter by creation date
+    ///
+    /// Only include issues and pull requests created at the given date, such as "2021-04-30". The
+    /// date can be compared with >, >=, < or <=, as in ">=2021-04-30", or be a range, as in
+    /// "2021-01-01..2021-04-30".
+    #[structopt(long)]
+    created: Option<DateFilter>,
+
+    /// Filter by date of last update
+    ///
+    /// Only include issues and pull requests last updated at the given date, such as
+    /// "<2021-04-30". The same comparisons and ranges as for --created can be used.
+    #[structopt(long)]
+    updated: Option<DateFilter>,
+
+    /// Only include issues and pull requests created since
+    ///
+    /// Only include issues and pull requests created on or after the given date, such as
+    /// "2021-04-30", or within the given time, such as "12h", "3d", "2w", "1m" (one month) or
+    /// "1y".
+    #[structopt(long, conflicts_with = "created")]
+    since: Option<When>,
+
+    /// Only include issues and pull requests created until
+    ///
+    /// Only include issues and pull requests created on or before the given date, or before the
+    /// given time, in the same format as for --since.
+    #[structopt(long, conflicts_with = "created")]
+    until: Option<When>,
+
+    /// Only include issues and pull requests updated since
+    ///
+    /// Only include issues and pull requests updated on or after the given date, or within the
+    /// given time, in the same format as for --since.
+    #[structopt(long, conflicts_with = "updated")]
+    updated_since: Option<When>,
+
+    /// Only include issues and pull requests that are stale
+    ///
+    /// Only include issues and pull requests that have not been updated since the given date, or
+    /// within the given time, such as "30d" for issues that have been idle for 30 days. The same
+    /// format as for --since is used.
+    #[structopt(long, conflicts_with = "updated")]
+    stale: Option<When>,
+
+    /// Filter by language of the repository
+    #[structopt(long)]
+    language: Option<String>,
+
+    /// Only include draft pull requests
+    #[structopt(long)]
+    draft: bool,
+
+    /// Only include pull requests that are ready for review, and not drafts
+    #[structopt(long, conflicts_with = "draft")]
+    ready: bool,
+
+    /// Filter pull requests by review status
+    ///
+    /// Only include pull requests with the given review status, which can be "none",
+    /// "required", "approved" or "changes_requested".
+    #[structopt(long)]
+    review: Option<Review>,
+
+    /// Filter pull requests by status of checks
+    ///
+    /// Only include pull requests where the combined status of checks for the latest commit is
+    /// "pending", "success" or "failure".
+    #[structopt(long)]
+    status: Option<Status>,
+
+    /// Only include pull requests that have been merged
+    ///
+    /// Only include pull requests that have been merged, and not those that were closed without
+    /// being merged. Since merged pull requests are closed, this can not be combined with
+    /// --open/-o.
+    #[structopt(long, conflicts_with = "open")]
+    merged: bool,
+
+    /// Filter issues by the reason for their state
+    ///
+    /// Only include issues that were closed or reopened with the given reason, which can be
+    /// "completed", "not_planned", "duplicate" or "reopened". Closed issues are listed unless
+    /// --open/-o is given or the reason is "reopened".
+    #[structopt(long)]
+    state_reason: Option<StateReason>,
 }
 
 #[derive(Debug, Copy, Clone)]
-enum Flag {
+pub enum Flag {
     True,
     False,
     Auto,
 }
 
+impl std::fmt::Display for Flag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output: &str = match self {
+            Flag::True => "true",
+            Flag::False => "false",
+            Flag::Auto => "auto",
+        };
+        write!(f, "{}", output)
+    }
+}
+
 impl FromStr for Flag {
     type Err = String;
 
@@ -152,7 +774,7 @@ impl FromStr for Flag {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone)]
 pub struct Verbosity(u8);
 
 impl Verbosity {
@@ -176,42 +798,135 @@ impl FromStr for Verbosity {
 }
 
 impl Config {
+    /// Parse the command line arguments, and resolve any settings from the configuration file
+    pub fn load() -> Result<Config, AppErr> {
+        let mut argv: Vec<String> = std::env::args().collect();
+        let mut cfg: Config = Config::from_iter(&argv);
+        if let Some(Command::Run(args)) = &cfg.command {
+            argv = saved::expand(argv, args)?;
+            cfg = Config::from_iter(&argv);
+        }
+        check_subcommand_position(&argv)?;
+        cfg.settings = Settings::read()?.profile(cfg.profile())?;
+        Ok(cfg)
+    }
+
+    pub fn command(&self) -> Option<&Command> {
+        self.command.as_ref()
+    }
+
+    /// Arguments for listing, which are either given to the `list` subcommand or at the top level
+    /// when no subcommand is given
+    fn list(&self) -> &ListArgs {
+        match &self.command {
+            Some(Command::List(args)) => args,
+            _ => &self.list,
+        }
+    }
+
+    pub fn profile(&self) -> Option<&str> {
+        self.global.profile.as_deref()
+    }
+
+    pub fn settings(&self) -> &Profile {
+        &self.settings
+    }
+
     pub fn token(&self) -> Result<String, AppErr> {
-        self.token.clone().ok_or(AppErr::MissingToken)
+        self.token_source().map(|(token, _)| token)
+    }
+
+    /// Token and where it was found, which is either from the command line or any of the sources
+    /// that `auth::token` looks in
+    pub fn token_source(&self) -> Result<(String, Source), AppErr> {
+        let token = self.token.get_or_init(|| match &self.global.token {
+            Some(token) => Some((token.clone(), Source::Argument)),
+            None => auth::token(&self.host()),
+        });
+        token.clone().ok_or(AppErr::MissingToken)
+    }
+
+    pub fn notify(&self, args: &NotifyArgs) -> Result<NotifyConfig, AppErr> {
+        let user: Username = self.username().ok_or(AppErr::MissingToken)?;
+        let config = NotifyConfig {
+            user: user.0,
+            targets: args.target.clone(),
+            interval: args.interval,
+            command: args.command.clone().or_else(|| self.settings.notify_command.clone()),
+            once: args.once,
+        };
+        Ok(config)
+    }
+
+    pub fn inbox(&self, args: &InboxArgs) -> InboxConfig {
+        InboxConfig {
+            reasons: args.reason.clone(),
+            all: args.all,
+            limit: args.limit,
+        }
+    }
+
+    pub fn client_id(&self, args: &LoginArgs) -> Result<String, AppErr> {
+        match args.client_id.as_ref().or(self.settings.oauth_client_id.as_ref()) {
+            Some(client_id) => Ok(client_id.clone()),
+            None => Err(AppErr::InvalidConfig(String::from(
+                "No OAuth client id, which is required with --web since giss has no OAuth app of \
+                 its own. Set it with --client-id, GISS_OAUTH_CLIENT_ID or oauth_client_id",
+            ))),
+        }
+    }
+
+    pub fn api_url(&self) -> Option<String> {
+        self.global.api_url.clone()
+    }
+
+    pub fn timeout(&self) -> Duration {
+        match self.global.timeout {
+            Some(timeout) => timeout.into(),
+            None => crate::api::DEFAULT_TIMEOUT,
+        }
+    }
+
+    pub fn host(&self) -> Host {
+        match self.global.host.as_ref().or(self.settings.host.as_ref()) {
+            Some(host) => host.clone(),
+            None => git::current_remote().map(|remote| remote.host).unwrap_or_default(),
+        }
     }
 
     pub fn username(&self) -> Option<Username> {
-        match &self.user {
+        match self.list().user.as_ref().or(self.settings.user.as_ref()) {
             Some(user) => Some(user.clone()),
-            None => match self.token.clone() {
-                Some(token) => futures::executor::block_on(Username::from_token(&token)).ok(),
-                None => None,
+            None => match self.token() {
+                Ok(token) => futures::executor::block_on(Username::from_token(&token)).ok(),
+                Err(_) => None,
             },
         }
     }
 
     pub fn target(&self) -> Result<Vec<Target>, AppErr> {
-        if self.target.is_empty() {
-            match read_repo_from_file() {
-                Some(repo) => match repo.parse::<Target>() {
-                    Ok(target) => Ok(vec![target]),
-                    Err(_) => Err(AppErr::InvalidTarget(repo)),
-                },
-                None => Err(AppErr::NoTarget),
-            }
-        } else {
-            Ok(self.target.clone())
+        let args: &ListArgs = self.list();
+        if !args.target.is_empty() {
+            return Ok(args.target.clone());
+        }
+        match &self.settings.targets {
+            Some(targets) => targets
+                .iter()
+                .map(|t| t.parse::<Target>().map_err(|_| AppErr::InvalidTarget(t.clone())))
+                .collect(),
+            None => Ok(vec![Target::current()?]),
         }
     }
 
     pub fn limit(&self) -> u32 {
-        self.limit
+        self.list().limit.or(self.settings.limit).unwrap_or(10)
     }
 
     pub fn state(&self) -> StateFilter {
-        if self.open && self.closed {
+        let args: &ListArgs = self.list();
+        if args.open && args.closed {
             StateFilter::All
-        } else if self.closed {
+        } else if args.closed || (!args.open && self.qualifiers().closed_only()) {
             StateFilter::Closed
         } else {
             StateFilter::Open
@@ -219,51 +934,120 @@ impl Config {
     }
 
     pub fn assigned_only(&self) -> bool {
-        self.assigned
+        let args: &ListArgs = self.list();
+        flag(args.assigned, args.no_assigned, self.settings.assigned)
     }
 
     fn all(&self) -> bool {
-        !self.issues && !self.pull_requests && !self.review_requests
+        let args: &ListArgs = self.list();
+        !args.issues && !args.pull_requests && !args.review_requests
     }
 
     pub fn issues(&self) -> bool {
-        self.issues || self.all()
+        self.list().issues || self.all()
     }
 
     pub fn reviews(&self) -> bool {
-        self.review_requests || self.all()
+        self.list().review_requests || self.all()
     }
 
     pub fn pulls(&self) -> bool {
-        self.pull_requests || self.all()
+        self.list().pull_requests || self.all()
     }
 
     pub fn sorting(&self) -> Sorting {
-        Sorting(self.sort_by.unwrap_or_default(), self.order.unwrap_or_default())
+        let property: Option<Property> = self.list().sort_by.or(self.settings.sort_by);
+        let order: Option<Order> = self.list().order.or(self.settings.order);
+        Sorting(property.unwrap_or_default(), order.unwrap_or_default())
     }
 
     pub fn search(&self) -> Option<String> {
-        self.search.clone()
+        self.list().search.clone()
+    }
+
+    pub fn query(&self) -> Option<String> {
+        self.list().query.clone()
+    }
+
+    pub fn explain(&self) -> bool {
+        self.list().explain
     }
 
     pub fn label(&self) -> Vec<String> {
-        self.labels.clone()
+        let labels: &Vec<String> = &self.list().labels;
+        match (labels.is_empty(), &self.settings.labels) {
+            (true, Some(labels)) => labels.clone(),
+            _ => labels.clone(),
+        }
     }
 
     pub fn project(&self) -> Option<Project> {
-        self.project.clone()
+        self.list().project.clone()
+    }
+
+    pub fn qualifiers(&self) -> Qualifiers {
+        let args: &QualifierArgs = &self.list().qualifiers;
+        Qualifiers {
+            author: args.author.clone(),
+            mentions: args.mentions.clone(),
+            commenter: args.commenter.clone(),
+            involves: args.involves.clone(),
+            reviewed_by: args.reviewed_by.clone(),
+            milestone: args.milestone.clone(),
+            missing: args.missing.clone(),
+            exclude_labels: args.exclude_label.clone(),
+            created: match (&args.since, &args.until) {
+                (None, None) => args.created.clone(),
+                (since, until) => DateFilter::range(since, until, true),
+            },
+            updated: match (&args.updated_since, &args.stale) {
+                (None, None) => args.updated.clone(),
+                (since, stale) => DateFilter::range(since, stale, false),
+            },
+            language: args.language.clone(),
+            draft: match (args.draft, args.ready) {
+                (true, _) => Some(true),
+                (false, true) => Some(false),
+                (false, false) => None,
+            },
+            review: args.review,
+            status: args.status,
+            merged: args.merged,
+            state_reason: args.state_reason,
+        }
     }
 
     pub fn show_links(&self) -> bool {
-        self.links
+        let args: &ListArgs = self.list();
+        flag(args.links, args.no_links, self.settings.links)
+    }
+
+    pub fn output(&self) -> OutputFormat {
+        self.list().output.or(self.settings.output).unwrap_or_default()
+    }
+
+    pub fn interactive(&self) -> bool {
+        self.list().interactive
     }
 
-    pub fn verbosity(&self) -> &Verbosity {
-        &self.verbosity
+    pub fn watch(&self) -> Option<Interval> {
+        self.list().watch
+    }
+
+    pub fn cache_mode(&self) -> CacheMode {
+        match (self.global.offline, self.global.refresh) {
+            (true, _) => CacheMode::Offline,
+            (false, true) => CacheMode::Refresh,
+            (false, false) => CacheMode::Default,
+        }
+    }
+
+    pub fn verbosity(&self) -> Verbosity {
+        self.global.verbosity.unwrap_or(Verbosity(1))
     }
 
     pub fn colors(&self) -> ColorChoice {
-        match self.colors {
+        match self.global.colors.or(self.settings.colors).unwrap_or(Flag::Auto) {
             Flag::True => ColorChoice::Always,
             Flag::False => ColorChoice::Never,
             Flag::Auto => ColorChoice::Auto,
@@ -271,6 +1055,55 @@ impl Config {
     }
 
     pub fn print_debug(&self) -> bool {
-        self.debug
+        self.global.debug
+    }
+}
+
+/// Check that no options or targets for listing are given before a subcommand, since they would
+/// otherwise be ignored in favour of the arguments of the subcommand
+fn check_subcommand_position(argv: &[String]) -> Result<(), AppErr> {
+    let matches = Config::clap().get_matches_from_safe(argv).map_err(|e| {
+        log::error!("Unable to parse arguments: {}", e);
+        AppErr::InvalidInput(e.message)
+    })?;
+    let name: &str = match matches.subcommand_name() {
+        Some(name) => name,
+        None => return Ok(()),
+    };
+    // The name of the subcommand may also be the value of an option, so any occurrence of it
+    // where only global options precede it is accepted
+    let only_globals: bool = argv
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, arg)| *arg == name)
+        .any(|(i, _)| GlobalArgs::from_iter_safe(&argv[..i]).is_ok());
+
+    match only_globals {
+        true => Ok(()),
+        false => Err(AppErr::InvalidInput(format!(
+            "Options and targets for listing must be given after the subcommand, as in \
+             'giss {} <options>'",
+            name
+        ))),
+    }
+}
+
+/// Resolve a flag which can be turned both on and off on the command line, with `default` from
+/// the configuration file used when neither is given
+fn flag(on: bool, off: bool, default: Option<bool>) -> bool {
+    match (on, off) {
+        (true, _) => true,
+        (false, true) => false,
+        (false, false) => default.unwrap_or(false),
+    }
+}
+
+/// Check that `args` are valid arguments for `giss list`, such as before they are saved
+pub fn validate_list_args(args: &[String]) -> Result<(), AppErr> {
+    let argv = ["giss", "list"].iter().map(|s| s.to_string()).chain(args.iter().cloned());
+    match Config::from_iter_safe(argv) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(AppErr::InvalidInput(e.message)),
     }
 }
@@ -0,0 +1,298 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, IsTerminal, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    cfg::CreateArgs,
+    git,
+    repository::{self, RepositoryMeta},
+    search::GraphQLQuery,
+    AppErr,
+};
+
+/// An issue that is yet to be created, as written by the user in an editor. When edited, the
+/// labels, assignees and milestone are put in a front matter, followed by the title on the first
+/// line and then the body.
+#[derive(Debug, Default)]
+pub struct Draft {
+    pub title: String,
+    pub body: String,
+    pub labels: Vec<String>,
+    pub assignees: Vec<String>,
+    pub milestone: Option<String>,
+}
+
+/// Issue template, read from `.github/ISSUE_TEMPLATE/*.md`
+#[derive(Debug)]
+pub struct Template {
+    pub name: String,
+    pub file: String,
+    pub draft: Draft,
+}
+
+#[derive(Debug, Deserialize)]
+struct Root {
+    data: Data,
+}
+
+#[derive(Debug, Deserialize)]
+struct Data {
+    #[serde(alias = "createIssue")]
+    create_issue: CreateIssue,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateIssue {
+    issue: CreatedIssue,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatedIssue {
+    pub url: String,
+}
+
+impl Draft {
+    /// Render the draft into the format that is edited by the user
+    pub fn render(&self) -> String {
+        format!(
+            "---\nlabels: {}\nassignees: {}\nmilestone: {}\n---\n{}\n\n{}",
+            self.labels.join(", "),
+            self.assignees.join(", "),
+            self.milestone.clone().unwrap_or_default(),
+            self.title,
+            self.body
+        )
+    }
+
+    /// Parse a draft that has been edited by the user
+    pub fn parse(content: &str) -> Result<Draft, AppErr> {
+        let (front_matter, text) = front_matter(content);
+        let text: &str = text.trim_start();
+        let (title, body) = match text.split_once('\n') {
+            Some((title, body)) => (title.trim(), body.trim()),
+            None => (text.trim(), ""),
+        };
+
+        if title.is_empty() {
+            return Err(AppErr::InvalidInput(String::from("Title must not be empty")));
+        }
+
+        let draft = Draft {
+            title: title.to_string(),
+            body: body.to_string(),
+            labels: list(front_matter.get("labels")),
+            assignees: list(front_matter.get("assignees")),
+            milestone: front_matter.get("milestone").map(|m| unquote(m)).filter(|m| !m.is_empty()),
+        };
+        Ok(draft)
+    }
+}
+
+impl Template {
+    fn read(path: &Path) -> Result<Template, AppErr> {
+        let content: String = std::fs::read_to_string(path)?;
+        let (front_matter, body) = front_matter(&content);
+        let file: String = path
+            .file_stem()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let name: String = match front_matter.get("name") {
+            Some(name) => unquote(name),
+            None => file.clone(),
+        };
+        let draft = Draft {
+            title: front_matter.get("title").map(|t| unquote(t)).unwrap_or_default(),
+            body: body.trim().to_string(),
+            labels: list(front_matter.get("labels")),
+            assignees: list(front_matter.get("assignees")),
+            milestone: None,
+        };
+        Ok(Template { name, file, draft })
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name) || self.file.eq_ignore_ascii_case(name)
+    }
+}
+
+/// Find all Markdown issue templates in the git repository of the current directory
+pub fn templates() -> Result<Vec<Template>, AppErr> {
+    let root: PathBuf = match git::work_tree()? {
+        Some(root) => root,
+        None => return Ok(Vec::with_capacity(0)),
+    };
+    let dir: PathBuf = root.join(".github").join("ISSUE_TEMPLATE");
+    if !dir.is_dir() {
+        return Ok(Vec::with_capacity(0));
+    }
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().map(|ext| ext == "md").unwrap_or(false))
+        .collect();
+    paths.sort();
+
+    paths.iter().map(|path| Template::read(path)).collect()
+}
+
+/// Create the draft for a new issue, from the arguments, any selected template and the user's
+/// editor. The editor is not opened if a title was given as an argument.
+pub fn draft(args: &CreateArgs) -> Result<Draft, AppErr> {
+    let mut draft: Draft = match select_template(templates()?, args.template.as_deref())? {
+        Some(template) => template.draft,
+        None => Draft::default(),
+    };
+
+    if !args.labels.is_empty() {
+        draft.labels = args.labels.clone();
+    }
+    if !args.assignees.is_empty() {
+        draft.assignees = args.assignees.clone();
+    }
+    if let Some(milestone) = &args.milestone {
+        draft.milestone = Some(milestone.clone());
+    }
+    if let Some(body) = &args.body {
+        draft.body = body.clone();
+    }
+
+    match &args.title {
+        Some(title) => {
+            draft.title = title.clone();
+            Ok(draft)
+        }
+        None => Draft::parse(&crate::editor::edit(&draft.render())?),
+    }
+}
+
+/// Select template by name, or let the user choose one if there are any templates and no name
+/// was given
+fn select_template(
+    templates: Vec<Template>,
+    name: Option<&str>,
+) -> Result<Option<Template>, AppErr> {
+    match name {
+        Some(name) => match templates.into_iter().find(|t| t.matches(name)) {
+            Some(template) => Ok(Some(template)),
+            None => Err(AppErr::NotFound(format!("template '{}'", name))),
+        },
+        None if templates.is_empty() || !std::io::stdin().is_terminal() => Ok(None),
+        None => {
+            let mut stderr = std::io::stderr();
+            for (i, template) in templates.iter().enumerate() {
+                writeln!(stderr, "{}) {}", i + 1, template.name)?;
+            }
+            write!(stderr, "Choose template (1-{}, blank for none): ", templates.len())?;
+            stderr.flush()?;
+
+            let mut choice = String::new();
+            std::io::stdin().lock().read_line(&mut choice)?;
+            let choice: &str = choice.trim();
+            if choice.is_empty() {
+                return Ok(None);
+            }
+            match choice.parse::<usize>() {
+                Ok(n) if n >= 1 && n <= templates.len() => {
+                    Ok(templates.into_iter().nth(n - 1))
+                }
+                _ => Err(AppErr::InvalidInput(format!("Invalid template choice '{}'", choice))),
+            }
+        }
+    }
+}
+
+pub async fn create_issue(
+    token: &str,
+    owner: &str,
+    name: &str,
+    draft: &Draft,
+) -> Result<CreatedIssue, AppErr> {
+    let repo: RepositoryMeta = repository::get_repository(token, owner, name).await?;
+    let label_ids: Vec<String> = repo.label_ids(token, &draft.labels).await?;
+    let assignee_ids: Vec<String> = repository::user_ids(token, &draft.assignees).await?;
+    let milestone_id: Option<String> = match &draft.milestone {
+        Some(milestone) => Some(repo.milestone_id(token, milestone).await?),
+        None => None,
+    };
+
+    let query = GraphQLQuery {
+        variables: json!({
+            "input": {
+                "repositoryId": repo.id,
+                "title": draft.title,
+                "body": draft.body,
+                "labelIds": label_ids,
+                "assigneeIds": assignee_ids,
+                "milestoneId": milestone_id,
+            }
+        }),
+        query: String::from(include_str!("../data/graphql/mutations/create_issue.graphql")),
+        operation_name: String::from("CreateIssue"),
+    };
+
+    let root: Root = crate::api::v4::request(token, query).await?;
+    Ok(root.data.create_issue.issue)
+}
+
+/// Split content into its front matter, if any, and the remaining text. The front matter is
+/// expected to be a simple subset of YAML, with one `key: value` pair per line, where lists may
+/// also be given as `[a, b]` or as `- item` on the following lines.
+fn front_matter(content: &str) -> (HashMap<String, String>, &str) {
+    let mut values: HashMap<String, String> = HashMap::new();
+    let mut lines = content.split_inclusive('\n');
+    let mut offset: usize = match lines.next() {
+        Some(first) if first.trim_end() == "---" => first.len(),
+        _ => return (values, content),
+    };
+
+    let mut key: Option<String> = None;
+    for line in lines {
+        offset += line.len();
+        let line: &str = line.trim_end();
+        if line == "---" {
+            return (values, &content[offset..]);
+        }
+        match (line.trim_start().strip_prefix("- "), &key) {
+            (Some(item), Some(key)) => {
+                let value: &mut String = values.entry(key.clone()).or_default();
+                if !value.is_empty() {
+                    value.push_str(", ");
+                }
+                value.push_str(item.trim());
+            }
+            _ => {
+                if let Some((k, v)) = line.split_once(':') {
+                    let k: String = k.trim().to_lowercase();
+                    values.insert(k.clone(), v.trim().to_string());
+                    key = Some(k);
+                }
+            }
+        }
+    }
+
+    // No end of the front matter was found, so treat it all as regular text
+    (HashMap::with_capacity(0), content)
+}
+
+fn list(value: Option<&String>) -> Vec<String> {
+    match value {
+        Some(value) => value
+            .trim()
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split(',')
+            .map(unquote)
+            .filter(|v| !v.is_empty())
+            .collect(),
+        None => Vec::with_capacity(0),
+    }
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches(|c| c == '"' || c == '\'').to_string()
+}
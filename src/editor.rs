@@ -0,0 +1,41 @@
+use std::{io::Write, process::Command};
+
+use tempfile::NamedTempFile;
+
+use crate::AppErr;
+
+/// Open the user's editor, as given by `$VISUAL` or `$EDITOR`, with `initial` as content. The
+/// content of the file once the editor has exited is returned.
+pub fn edit(initial: &str) -> Result<String, AppErr> {
+    let mut file: NamedTempFile = tempfile::Builder::new()
+        .prefix("giss-")
+        .suffix(".md")
+        .tempfile()?;
+    file.write_all(initial.as_bytes())?;
+    file.flush()?;
+
+    let editor: String = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| String::from("vi"));
+
+    // The editor may be given with arguments, such as "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program: &str = parts.next().ok_or(AppErr::EditorError)?;
+    log::debug!("Opening {:?} with editor '{}'", file.path(), editor);
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(file.path())
+        .status()
+        .map_err(|e| {
+            log::error!("Unable to start editor '{}': {}", editor, e);
+            AppErr::EditorError
+        })?;
+
+    if !status.success() {
+        log::error!("Editor '{}' exited with {}", editor, status);
+        return Err(AppErr::EditorError);
+    }
+
+    Ok(std::fs::read_to_string(file.path())?)
+}
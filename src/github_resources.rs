@@ -1,22 +1,93 @@
 pub mod ghrs {
-    use serde::Deserialize;
+    use serde::{Deserialize, Serialize};
+    use std::{fmt, str::FromStr};
 
-    #[derive(Debug, Deserialize, PartialEq)]
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Copy, Clone)]
     pub enum State {
-        #[serde(rename(deserialize = "open"))]
+        #[serde(rename = "open")]
         #[serde(alias = "OPEN")]
         Open,
-        #[serde(rename(deserialize = "closed"))]
+        #[serde(rename = "closed")]
         #[serde(alias = "CLOSED")]
-        #[serde(alias = "MERGED")]
         Closed,
+        /// Only pull requests can be merged, which also means that they are closed
+        #[serde(rename = "merged")]
+        #[serde(alias = "MERGED")]
+        Merged,
     }
 
-    pub trait Closeable {
-        fn is_open(&self) -> bool;
+    impl fmt::Display for State {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let output: &str = match self {
+                State::Open => "open",
+                State::Closed => "closed",
+                State::Merged => "merged",
+            };
+            write!(f, "{}", output)
+        }
+    }
+
+    /// Reason for the current state of an issue, which is only available for issues and not for
+    /// pull requests
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Copy, Clone)]
+    pub enum StateReason {
+        #[serde(rename = "completed")]
+        #[serde(alias = "COMPLETED")]
+        Completed,
+        #[serde(rename = "not_planned")]
+        #[serde(alias = "NOT_PLANNED")]
+        NotPlanned,
+        #[serde(rename = "duplicate")]
+        #[serde(alias = "DUPLICATE")]
+        Duplicate,
+        #[serde(rename = "reopened")]
+        #[serde(alias = "REOPENED")]
+        Reopened,
+        /// Any reason that was added to GitHub's API after this was written
+        #[serde(rename = "other")]
+        #[serde(other)]
+        Other,
+    }
+
+    impl StateReason {
+        /// Whether an issue with this reason is closed
+        pub fn is_closed(&self) -> bool {
+            match self {
+                StateReason::Completed | StateReason::NotPlanned | StateReason::Duplicate => true,
+                StateReason::Reopened | StateReason::Other => false,
+            }
+        }
+    }
+
+    impl FromStr for StateReason {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.to_lowercase().replace(['-', ' '], "_").as_str() {
+                "completed" => Ok(StateReason::Completed),
+                "not_planned" => Ok(StateReason::NotPlanned),
+                "duplicate" => Ok(StateReason::Duplicate),
+                "reopened" => Ok(StateReason::Reopened),
+                _ => Err(format!(
+                    "Invalid state reason '{}', expected completed, not_planned, duplicate or \
+                     reopened",
+                    s
+                )),
+            }
+        }
     }
 
-    pub trait ModDate: Ord {
-        fn mod_time(&self) -> u64;
+    /// Reason as it is written in a search query, and shown to the user
+    impl fmt::Display for StateReason {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let output: &str = match self {
+                StateReason::Completed => "completed",
+                StateReason::NotPlanned => "not planned",
+                StateReason::Duplicate => "duplicate",
+                StateReason::Reopened => "reopened",
+                StateReason::Other => "other",
+            };
+            write!(f, "{}", output)
+        }
     }
 }
@@ -0,0 +1,217 @@
+use std::{fmt, str::FromStr};
+
+use reqwest::Method;
+use serde::Deserialize;
+
+use crate::{api::v3, AppErr};
+
+/// Number of notification threads fetched per page, which is the maximum of the API
+const PAGE_SIZE: u32 = 50;
+
+/// Why a notification thread is in the inbox, see GitHub's documentation on [notification
+/// reasons](https://docs.github.com/en/rest/activity/notifications#about-notification-reasons)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    ApprovalRequested,
+    Assign,
+    Author,
+    CiActivity,
+    Comment,
+    Invitation,
+    Manual,
+    MemberFeatureRequested,
+    Mention,
+    ReviewRequested,
+    SecurityAlert,
+    SecurityAdvisoryCredit,
+    StateChange,
+    Subscribed,
+    TeamMention,
+    /// Any reason that is not known when this was written
+    #[serde(other)]
+    Other,
+}
+
+const REASONS: [(Reason, &str); 15] = [
+    (Reason::ApprovalRequested, "approval_requested"),
+    (Reason::Assign, "assign"),
+    (Reason::Author, "author"),
+    (Reason::CiActivity, "ci_activity"),
+    (Reason::Comment, "comment"),
+    (Reason::Invitation, "invitation"),
+    (Reason::Manual, "manual"),
+    (Reason::MemberFeatureRequested, "member_feature_requested"),
+    (Reason::Mention, "mention"),
+    (Reason::ReviewRequested, "review_requested"),
+    (Reason::SecurityAlert, "security_alert"),
+    (Reason::SecurityAdvisoryCredit, "security_advisory_credit"),
+    (Reason::StateChange, "state_change"),
+    (Reason::Subscribed, "subscribed"),
+    (Reason::TeamMention, "team_mention"),
+];
+
+impl FromStr for Reason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s: String = s.trim().to_lowercase();
+        match REASONS.iter().find(|(_, name)| *name == s) {
+            Some((reason, _)) => Ok(*reason),
+            None => Err(format!("Invalid reason '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name: &str = REASONS
+            .iter()
+            .find(|(reason, _)| reason == self)
+            .map(|(_, name)| *name)
+            .unwrap_or("other");
+        write!(f, "{}", name)
+    }
+}
+
+/// A notification thread, which is about a single issue, pull request, release, discussion or
+/// similar
+#[derive(Debug, Deserialize)]
+pub struct Thread {
+    pub id: String,
+    pub reason: Reason,
+    pub unread: bool,
+    pub subject: Subject,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Subject {
+    pub title: String,
+    /// URL in the REST API of the subject, which is missing for some types of subjects
+    pub url: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Repository {
+    pub full_name: String,
+}
+
+impl Thread {
+    /// Number of the issue or pull request that the thread is about, if it is about either
+    pub fn number(&self) -> Option<u32> {
+        match self.subject.kind.as_str() {
+            "Issue" | "PullRequest" => self.subject.url.as_ref()?.rsplit('/').next()?.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+pub struct InboxConfig {
+    pub reasons: Vec<Reason>,
+    pub all: bool,
+    pub limit: u32,
+}
+
+/// List notification threads, most recently updated first. Only unread threads are included,
+/// unless `all` is set. Since the API can not filter by reason, pages are fetched until there
+/// are `limit` threads with any of the given reasons, or there are no more threads.
+pub async fn list(token: &str, cfg: &InboxConfig) -> Result<Vec<Thread>, AppErr> {
+    let mut threads: Vec<Thread> = Vec::new();
+    let mut page: u32 = 1;
+    loop {
+        let path: String = format!(
+            "/notifications?all={}&per_page={}&page={}",
+            cfg.all, PAGE_SIZE, page
+        );
+        let fetched: Vec<Thread> = v3::get(token, &path).await?;
+        let last_page: bool = fetched.len() < PAGE_SIZE as usize;
+        threads.extend(
+            fetched
+                .into_iter()
+                .filter(|t| cfg.reasons.is_empty() || cfg.reasons.contains(&t.reason)),
+        );
+        if last_page || threads.len() >= cfg.limit as usize {
+            threads.truncate(cfg.limit as usize);
+            return Ok(threads);
+        }
+        page += 1;
+    }
+}
+
+/// Mark a notification thread as read
+pub async fn mark_read(token: &str, id: u64) -> Result<(), AppErr> {
+    let path: String = format!("/notifications/threads/{}", id);
+    v3::update(token, Method::PATCH, &path).await?;
+    Ok(())
+}
+
+/// Mark all notification threads as read
+pub async fn mark_all_read(token: &str) -> Result<(), AppErr> {
+    v3::update(token, Method::PUT, "/notifications").await?;
+    Ok(())
+}
+
+/// Unsubscribe from a notification thread, so there will be no further notifications about it
+/// until the user is mentioned or participates again. The thread is also marked as read.
+pub async fn unsubscribe(token: &str, id: u64) -> Result<(), AppErr> {
+    let path: String = format!("/notifications/threads/{}/subscription", id);
+    v3::update(token, Method::DELETE, &path).await?;
+    mark_read(token, id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thread(kind: &str, url: Option<&str>) -> Thread {
+        Thread {
+            id: String::from("1"),
+            reason: Reason::Mention,
+            unread: true,
+            subject: Subject {
+                title: String::from("Crash on start"),
+                url: url.map(String::from),
+                kind: String::from(kind),
+            },
+            repository: Repository {
+                full_name: String::from("mantono/giss"),
+            },
+        }
+    }
+
+    #[test]
+    fn reasons_are_parsed_and_displayed() {
+        for (reason, name) in REASONS.iter() {
+            assert_eq!(Ok(*reason), Reason::from_str(name));
+            assert_eq!(*name, reason.to_string());
+        }
+        assert_eq!(Ok(Reason::ReviewRequested), Reason::from_str(" Review_Requested "));
+        assert_eq!("other", Reason::Other.to_string());
+    }
+
+    #[test]
+    fn unknown_reason_is_rejected() {
+        assert_eq!(Err(String::from("Invalid reason 'other'")), Reason::from_str("other"));
+        assert!(Reason::from_str("").is_err());
+    }
+
+    #[test]
+    fn number_of_issue_or_pull_request() {
+        let url = "https://api.github.com/repos/mantono/giss/issues/12";
+        assert_eq!(Some(12), thread("Issue", Some(url)).number());
+        let url = "https://api.github.com/repos/mantono/giss/pulls/7";
+        assert_eq!(Some(7), thread("PullRequest", Some(url)).number());
+    }
+
+    #[test]
+    fn no_number_for_other_subjects() {
+        let url = "https://api.github.com/repos/mantono/giss/releases/3";
+        assert_eq!(None, thread("Release", Some(url)).number());
+        assert_eq!(None, thread("Issue", None).number());
+        let url = "https://api.github.com/repos/mantono/giss/issues/latest";
+        assert_eq!(None, thread("Issue", Some(url)).number());
+    }
+}
@@ -0,0 +1,294 @@
+use std::io::{Stdout, Write};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use termcolor::NoColor;
+
+use crate::{browser, issue::Issue, show::IssueDetails, ui, ui::DisplayConfig, AppErr};
+
+/// Browse issues and pull requests in an interactive full-screen view, where the list can be
+/// scrolled, filtered and refreshed, and each issue can be viewed in detail or opened in the
+/// browser.
+///
+/// Issues are (re)loaded with `load`, while the details of an issue are fetched with `details`.
+pub fn browse<L, D>(cfg: &DisplayConfig, mut load: L, mut details: D) -> Result<(), AppErr>
+where
+    L: FnMut() -> Result<Vec<Issue>, AppErr>,
+    D: FnMut(&Issue) -> Result<IssueDetails, AppErr>,
+{
+    let mut browser = Browser::new(cfg, load()?);
+    let mut screen = Screen::enter()?;
+
+    loop {
+        let (width, height) = terminal::size()?;
+        browser.draw(&mut screen.stdout, width as usize, height as usize)?;
+
+        let key: KeyEvent = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+
+        if browser.filtering {
+            browser.edit_filter(key);
+            continue;
+        }
+
+        let page: usize = std::cmp::max(1, (height as usize).saturating_sub(1));
+        match &mut browser.view {
+            View::Details { offset, lines } => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc | KeyCode::Backspace | KeyCode::Left => {
+                    browser.view = View::List
+                }
+                KeyCode::Down | KeyCode::Char('j') => *offset = (*offset + 1).min(lines.len()),
+                KeyCode::Up | KeyCode::Char('k') => *offset = offset.saturating_sub(1),
+                KeyCode::PageDown | KeyCode::Char(' ') => {
+                    *offset = (*offset + page).min(lines.len())
+                }
+                KeyCode::PageUp => *offset = offset.saturating_sub(page),
+                KeyCode::Char('o') => browser.open(),
+                _ => {}
+            },
+            View::List => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                KeyCode::Down | KeyCode::Char('j') => browser.move_by(1),
+                KeyCode::Up | KeyCode::Char('k') => browser.move_by(-1),
+                KeyCode::PageDown => browser.move_by(page as isize),
+                KeyCode::PageUp => browser.move_by(-(page as isize)),
+                KeyCode::Home | KeyCode::Char('g') => browser.selected = 0,
+                KeyCode::End | KeyCode::Char('G') => browser.move_by(isize::MAX / 2),
+                KeyCode::Char('/') => browser.filtering = true,
+                KeyCode::Char('o') => browser.open(),
+                KeyCode::Enter | KeyCode::Right => {
+                    if let Some(index) = browser.visible.get(browser.selected).copied() {
+                        browser.status = Some(String::from("Loading..."));
+                        browser.draw(&mut screen.stdout, width as usize, height as usize)?;
+                        match details(&browser.issues[index]) {
+                            Ok(issue) => browser.show(&issue, width as usize),
+                            Err(e) => browser.status = Some(format!("Error: {:?}", e)),
+                        }
+                    }
+                }
+                KeyCode::Char('r') => {
+                    browser.status = Some(String::from("Refreshing..."));
+                    browser.draw(&mut screen.stdout, width as usize, height as usize)?;
+                    match load() {
+                        Ok(issues) => browser.replace(issues),
+                        Err(e) => browser.status = Some(format!("Error: {:?}", e)),
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    Ok(())
+}
+
+enum View {
+    List,
+    Details { lines: Vec<String>, offset: usize },
+}
+
+struct Browser<'a> {
+    cfg: &'a DisplayConfig,
+    issues: Vec<Issue>,
+    /// Each issue rendered as a line, in the same format as when listed non-interactively
+    rows: Vec<String>,
+    /// Indices of the issues that matches the current filter
+    visible: Vec<usize>,
+    selected: usize,
+    offset: usize,
+    filter: String,
+    filtering: bool,
+    status: Option<String>,
+    view: View,
+}
+
+impl<'a> Browser<'a> {
+    fn new(cfg: &'a DisplayConfig, issues: Vec<Issue>) -> Browser<'a> {
+        let mut browser = Browser {
+            cfg,
+            issues: Vec::with_capacity(0),
+            rows: Vec::with_capacity(0),
+            visible: Vec::with_capacity(0),
+            selected: 0,
+            offset: 0,
+            filter: String::new(),
+            filtering: false,
+            status: None,
+            view: View::List,
+        };
+        browser.replace(issues);
+        browser
+    }
+
+    fn replace(&mut self, issues: Vec<Issue>) {
+        self.rows = issues.iter().map(|i| render_row(i, self.cfg)).collect();
+        self.issues = issues;
+        self.status = None;
+        self.apply_filter();
+    }
+
+    fn apply_filter(&mut self) {
+        let filter: String = self.filter.to_lowercase();
+        self.visible = self
+            .issues
+            .iter()
+            .zip(self.rows.iter())
+            .enumerate()
+            .filter(|(_, (issue, row))| {
+                row.to_lowercase().contains(&filter)
+                    || issue.title.to_lowercase().contains(&filter)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        self.selected = self.selected.min(self.visible.len().saturating_sub(1));
+    }
+
+    fn edit_filter(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.filtering = false,
+            KeyCode::Esc => {
+                self.filtering = false;
+                self.filter.clear();
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+            }
+            KeyCode::Char(c) => self.filter.push(c),
+            _ => return,
+        }
+        self.apply_filter();
+    }
+
+    fn move_by(&mut self, steps: isize) {
+        let last: isize = self.visible.len().saturating_sub(1) as isize;
+        let selected: isize = (self.selected as isize).saturating_add(steps);
+        self.selected = selected.clamp(0, last) as usize;
+    }
+
+    fn selected_issue(&self) -> Option<&Issue> {
+        self.visible.get(self.selected).map(|i| &self.issues[*i])
+    }
+
+    fn open(&mut self) {
+        if let Some(issue) = self.selected_issue() {
+            let link: String = issue.link();
+            self.status = match browser::open(&link) {
+                Ok(_) => Some(format!("Opened {}", link)),
+                Err(e) => Some(format!("Unable to open {}: {:?}", link, e)),
+            };
+        }
+    }
+
+    fn show(&mut self, issue: &IssueDetails, width: usize) {
+        let mut buffer = NoColor::new(Vec::<u8>::new());
+        ui::write_details(&mut buffer, issue);
+        let text: String = String::from_utf8_lossy(&buffer.into_inner()).to_string();
+        let lines: Vec<String> = text.lines().flat_map(|line| wrap(line, width)).collect();
+        self.status = None;
+        self.view = View::Details { lines, offset: 0 };
+    }
+
+    fn draw(&mut self, out: &mut Stdout, width: usize, height: usize) -> Result<(), AppErr> {
+        let rows: usize = height.saturating_sub(1);
+        queue!(out, terminal::Clear(ClearType::All))?;
+
+        match &self.view {
+            View::List => {
+                if self.selected < self.offset {
+                    self.offset = self.selected;
+                } else if self.selected >= self.offset + rows {
+                    self.offset = self.selected + 1 - rows;
+                }
+
+                let lines = self.visible.iter().enumerate().skip(self.offset).take(rows);
+                for (line, (position, index)) in lines.enumerate() {
+                    queue!(out, cursor::MoveTo(0, line as u16))?;
+                    if position == self.selected {
+                        queue!(out, SetAttribute(Attribute::Reverse))?;
+                    }
+                    queue!(out, Print(truncate(&self.rows[*index], width)))?;
+                    queue!(out, SetAttribute(Attribute::Reset))?;
+                }
+            }
+            View::Details { lines, offset } => {
+                for (line, text) in lines.iter().skip(*offset).take(rows).enumerate() {
+                    queue!(out, cursor::MoveTo(0, line as u16), Print(text))?;
+                }
+            }
+        }
+
+        let status: String = match (&self.view, self.filtering, &self.status) {
+            (_, true, _) => format!("/{}", self.filter),
+            (_, false, Some(status)) => status.clone(),
+            (View::List, false, None) => format!(
+                "{}/{} | / filter  enter details  o open  r refresh  q quit{}",
+                self.visible.len(),
+                self.issues.len(),
+                match self.filter.is_empty() {
+                    true => String::new(),
+                    false => format!(" | filter: {}", self.filter),
+                }
+            ),
+            (View::Details { .. }, false, None) => {
+                String::from("j/k scroll  o open  q back")
+            }
+        };
+        queue!(
+            out,
+            cursor::MoveTo(0, height.saturating_sub(1) as u16),
+            SetAttribute(Attribute::Reverse),
+            Print(format!("{:<width$}", truncate(&status, width), width = width)),
+            SetAttribute(Attribute::Reset)
+        )?;
+
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Alternate screen in raw mode, which is restored when dropped
+struct Screen {
+    stdout: Stdout,
+}
+
+impl Screen {
+    fn enter() -> Result<Screen, AppErr> {
+        let mut stdout: Stdout = std::io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(stdout, EnterAlternateScreen, cursor::Hide)?;
+        Ok(Screen { stdout })
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(self.stdout, cursor::Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn render_row(issue: &Issue, cfg: &DisplayConfig) -> String {
+    let mut buffer = NoColor::new(Vec::<u8>::new());
+    ui::print_issue(&mut buffer, issue, true, cfg);
+    String::from_utf8_lossy(&buffer.into_inner()).trim_end().to_string()
+}
+
+fn truncate(line: &str, width: usize) -> String {
+    line.chars().take(width).collect()
+}
+
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    match chars.is_empty() || width == 0 {
+        true => vec![String::new()],
+        false => chars.chunks(width).map(|c| c.iter().collect()).collect(),
+    }
+}
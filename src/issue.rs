@@ -15,9 +15,19 @@ pub struct Data {
 
 #[derive(Debug, Deserialize)]
 pub struct Search {
+    #[serde(alias = "pageInfo")]
+    pub page_info: PageInfo,
     pub edges: Vec<Node>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PageInfo {
+    #[serde(alias = "hasNextPage")]
+    pub has_next_page: bool,
+    #[serde(alias = "endCursor")]
+    pub end_cursor: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Node {
     pub node: Issue,
@@ -39,23 +49,31 @@ pub struct Issue {
     #[serde(alias = "issueState")]
     #[serde(alias = "pullRequestState")]
     pub state: ghrs::State,
+    #[serde(alias = "stateReason")]
+    pub state_reason: Option<ghrs::StateReason>,
     pub comments: Comments,
     pub reactions: Reactions,
     pub assignees: AssigneeNode,
     #[serde(alias = "reviewRequests")]
     pub review_requets: Option<ReviewRequestNode>,
+    #[serde(alias = "isDraft")]
+    pub is_draft: Option<bool>,
+    #[serde(alias = "reviewDecision")]
+    pub review_decision: Option<String>,
+    pub mergeable: Option<String>,
+    pub additions: Option<u32>,
+    pub deletions: Option<u32>,
+    #[serde(alias = "baseRefName")]
+    pub base_branch: Option<String>,
+    #[serde(alias = "headRefName")]
+    pub head_branch: Option<String>,
+    pub commits: Option<CommitNode>,
     pub labels: LabelNode,
     pub repository: Repository,
     #[serde(alias = "__typename")]
     pub kind: Type,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct Team {
-    pub name: String,
-    pub members: Vec<UserFields>,
-}
-
 impl PartialEq for Issue {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id
@@ -77,13 +95,23 @@ impl Issue {
     }
 
     pub fn link(&self) -> String {
-        let repo: &String = &self.repository.name_with_owner;
-        let kind: &str = match self.kind {
-            Type::Issue => "issues",
-            Type::PullRequest | Type::ReviewRequest => "pull",
-        };
-        let number: u32 = self.number;
-        format!("https://github.com/{}/{}/{}", repo, kind, number)
+        self.url.clone()
+    }
+
+    /// Combined state of all checks and statuses on the last commit of a pull request, such as
+    /// `SUCCESS`, `FAILURE` or `PENDING`
+    pub fn checks(&self) -> Option<&str> {
+        self.commits
+            .as_ref()
+            .and_then(|c| c.nodes.last())
+            .and_then(|c| c.commit.status_check_rollup.as_ref())
+            .map(|rollup| rollup.state.as_str())
+    }
+
+    /// Whether a pull request has conflicts with its base branch. This is false when GitHub has
+    /// not yet computed if it can be merged.
+    pub fn has_conflicts(&self) -> bool {
+        self.mergeable.as_deref() == Some("CONFLICTING")
     }
 }
 
@@ -121,6 +149,27 @@ pub struct Reactions {
     pub total_count: u32,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CommitNode {
+    pub nodes: Vec<CommitWrapper>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitWrapper {
+    pub commit: Commit,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Commit {
+    #[serde(alias = "statusCheckRollup")]
+    pub status_check_rollup: Option<StatusCheckRollup>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatusCheckRollup {
+    pub state: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct AssigneeNode {
     pub nodes: Vec<UserFields>,
@@ -166,11 +215,3 @@ impl Assignable {
     }
 }
 
-impl ghrs::Closeable for Issue {
-    fn is_open(&self) -> bool {
-        match self.state {
-            ghrs::State::Open => true,
-            ghrs::State::Closed => false,
-        }
-    }
-}
@@ -1,9 +1,11 @@
 use crate::search::{GraphQLQuery, SearchIssues, SearchQuery, Type};
 use crate::{
     api::ApiError,
+    cache::CacheMode,
     cfg::Config,
-    issue::{Issue, Root},
+    issue::{Issue, Root, Search},
     project::Project,
+    qualifier::Qualifiers,
     sort::Sorting,
     AppErr,
 };
@@ -14,7 +16,10 @@ use std::{
     time::Instant,
 };
 
-#[derive(Debug)]
+/// Maximum number of nodes that GitHub allows to be fetched in a single page
+const MAX_PAGE_SIZE: u32 = 100;
+
+#[derive(Debug, Clone)]
 pub struct FilterConfig {
     assigned_only: bool,
     pull_requests: bool,
@@ -22,26 +27,13 @@ pub struct FilterConfig {
     issues: bool,
     labels: Vec<String>,
     project: Option<Project>,
+    qualifiers: Qualifiers,
     sorting: Sorting,
     search: Option<String>,
+    query: Option<String>,
     state: StateFilter,
     limit: u32,
-}
-
-impl FilterConfig {
-    pub fn types(&self) -> Vec<Type> {
-        let mut types: Vec<Type> = Vec::with_capacity(3);
-        if self.issues {
-            types.push(Type::Issue)
-        }
-        if self.pull_requests {
-            types.push(Type::PullRequest)
-        }
-        if self.review_requests {
-            types.push(Type::ReviewRequest)
-        }
-        types
-    }
+    cache: CacheMode,
 }
 
 impl From<&Config> for FilterConfig {
@@ -52,11 +44,31 @@ impl From<&Config> for FilterConfig {
             review_requests: cfg.reviews(),
             labels: cfg.label(),
             project: cfg.project(),
+            qualifiers: cfg.qualifiers(),
             sorting: cfg.sorting(),
             search: cfg.search(),
+            query: cfg.query(),
             issues: cfg.issues(),
             state: cfg.state(),
             limit: cfg.limit(),
+            cache: cfg.cache_mode(),
+        }
+    }
+}
+
+impl FilterConfig {
+    /// Use the given `CacheMode` instead of the configured one, such as when refreshing
+    pub fn with_cache(self, cache: CacheMode) -> FilterConfig {
+        FilterConfig { cache, ..self }
+    }
+
+    /// Whether issues, pull requests or review requests should be searched for. Issues are not
+    /// searched for if any of the qualifiers only applies to pull requests.
+    fn includes(&self, kind: Type) -> bool {
+        match kind {
+            Type::Issue => self.issues && !self.qualifiers.pull_requests_only(),
+            Type::PullRequest => self.pull_requests && !self.qualifiers.issues_only(),
+            Type::ReviewRequest => self.review_requests && !self.qualifiers.issues_only(),
         }
     }
 }
@@ -92,21 +104,21 @@ pub async fn list_issues(
     let start = Instant::now();
 
     let issues = async {
-        if config.issues {
+        if config.includes(Type::Issue) {
             req_and_send(Type::Issue, &channel, &user, targets, token, config).await?;
         }
         Ok::<(), AppErr>(())
     };
 
     let pulls = async {
-        if config.pull_requests {
+        if config.includes(Type::PullRequest) {
             req_and_send(Type::PullRequest, &channel, &user, targets, token, config).await?;
         }
         Ok::<(), AppErr>(())
     };
 
     let reviews = async {
-        if config.review_requests {
+        if config.includes(Type::ReviewRequest) && user.is_some() {
             req_and_send(Type::ReviewRequest, &channel, &user, targets, token, config).await?;
         }
         Ok::<(), AppErr>(())
@@ -121,6 +133,22 @@ pub async fn list_issues(
     Ok(())
 }
 
+/// The query for the first page of each search that `list_issues` would make with the same
+/// arguments, without sending any of them. Review requests are left out when there is no user.
+pub fn queries(
+    user: &Option<Username>,
+    targets: &[Target],
+    config: &FilterConfig,
+) -> Vec<(Type, GraphQLQuery)> {
+    let user: Option<String> = user.clone().map(|u| u.0);
+    [Type::Issue, Type::PullRequest, Type::ReviewRequest]
+        .iter()
+        .filter(|kind| config.includes(**kind))
+        .filter(|kind| !matches!(kind, Type::ReviewRequest) || user.is_some())
+        .map(|kind| (*kind, create_query(*kind, &user, targets, config).build()))
+        .collect()
+}
+
 async fn req_and_send(
     kind: Type,
     channel: &SyncSender<Issue>,
@@ -129,11 +157,30 @@ async fn req_and_send(
     token: &str,
     config: &FilterConfig,
 ) -> Result<(), AppErr> {
-    let query: SearchIssues = create_query(kind, &user, targets, config);
-    let issues: Vec<Issue> = api_request(query, token).await?;
+    let mut query: SearchIssues = create_query(kind, user, targets, config);
+    let mut remaining: u32 = config.limit;
+
+    while remaining > 0 {
+        query.limit = std::cmp::min(remaining, MAX_PAGE_SIZE);
+        let page: Search = api_request(&query, token, config.cache).await?;
+        let cursor: Option<String> = match page.page_info.has_next_page {
+            true => page.page_info.end_cursor,
+            false => None,
+        };
+
+        if page.edges.is_empty() {
+            break;
+        }
 
-    for issue in issues {
-        channel.send(issue)?;
+        for issue in page.edges.into_iter().map(|n| n.node).take(remaining as usize) {
+            channel.send(issue)?;
+            remaining -= 1;
+        }
+
+        match cursor {
+            Some(cursor) => query.cursor = Some(cursor),
+            None => break,
+        }
     }
 
     Ok(())
@@ -165,8 +212,11 @@ fn create_query(kind: Type, user: &Option<String>, targets: &[Target], config: &
         labels: config.labels.clone(),
         project: config.project.clone(),
         targets: targets.to_vec(),
+        qualifiers: config.qualifiers.clone(),
         search: config.search.clone(),
-        limit: config.limit,
+        query: config.query.clone(),
+        limit: std::cmp::min(config.limit, MAX_PAGE_SIZE),
+        cursor: None,
     }
 }
 
@@ -180,7 +230,10 @@ impl From<ApiError> for AppErr {
     fn from(err: ApiError) -> Self {
         log::error!("{:?}", err);
         match err {
-            ApiError::NoResponse(_) => AppErr::ApiError,
+            ApiError::NoResponse(_) | ApiError::InvalidResponse(_) => AppErr::ApiError,
+            ApiError::NotCached => AppErr::NotCached,
+            ApiError::RateLimited(_) => AppErr::RateLimited,
+            ApiError::MissingScope(scope) => AppErr::MissingScope(scope),
             ApiError::Response(code) => match code {
                 429 => AppErr::RateLimited,
                 _ => AppErr::ApiError,
@@ -189,9 +242,12 @@ impl From<ApiError> for AppErr {
     }
 }
 
-async fn api_request(search: SearchIssues, token: &str) -> Result<Vec<Issue>, ApiError> {
+async fn api_request(
+    search: &SearchIssues,
+    token: &str,
+    cache: CacheMode,
+) -> Result<Search, ApiError> {
     let query: GraphQLQuery = search.build();
-    let issues: Root = crate::api::v4::request(token, query).await?;
-    let issues: Vec<Issue> = issues.data.search.edges.into_iter().map(|n| n.node).collect();
-    Ok(issues)
+    let root: Root = crate::cache::request(token, query, cache).await?;
+    Ok(root.data.search)
 }
@@ -6,65 +6,308 @@ extern crate regex;
 extern crate structopt;
 
 mod api;
-mod args;
+mod auth;
+mod browser;
+mod cache;
 mod cfg;
+mod create;
+mod editor;
+mod git;
 mod github_resources;
+mod inbox;
+mod interactive;
 mod issue;
 mod list;
 mod logger;
+mod notify;
+mod oauth;
+mod output;
 mod project;
+mod qualifier;
+mod repository;
+mod saved;
 mod search;
+mod settings;
+mod show;
 mod sort;
 mod target;
+mod triage;
 mod ui;
 mod user;
+mod watch;
 
-use crate::structopt::StructOpt;
-use cfg::Config;
+use api::Host;
+use auth::Access;
+use cache::CacheMode;
+use cfg::{
+    AssignArgs, AuthCommand, Command, CommentArgs, Config, ConfigCommand, CreateArgs, InboxArgs,
+    InboxCommand, LabelArgs, NotifyArgs, SaveArgs, SavedCommand, ShowArgs, StateArgs,
+};
 use issue::Issue;
 use list::FilterConfig;
 use logger::setup_logging;
-use target::Target;
+use oauth::AccessToken;
+use target::{IssueRef, Target};
 use tokio::runtime::Runtime;
 use ui::DisplayConfig;
-use user::Username;
+use user::{User, Username};
 
 #[tokio::main]
 async fn main() -> Result<(), AppErr> {
-    let cfg: Config = Config::from_args();
+    let cfg: Config = Config::load()?;
 
     if cfg.print_debug() {
         println!("{}", include_str!("../target/build_data"));
         return Ok(());
     }
 
-    setup_logging(cfg.verbosity());
+    setup_logging(&cfg.verbosity());
+    api::set_host(cfg.host());
+    api::set_api_url(cfg.api_url());
+    api::set_timeout(cfg.timeout());
+
+    match cfg.command() {
+        // A saved search is run by replacing its name with its arguments, see `Config::load`
+        None | Some(Command::List(_)) | Some(Command::Run(_)) => run_list(&cfg).await,
+        Some(Command::Show(args)) => run_show(&cfg, args).await,
+        Some(Command::Create(args)) => run_create(&cfg, args).await,
+        Some(Command::Comment(args)) => run_comment(&cfg, args).await,
+        Some(Command::Close(args)) => run_close(&cfg, args, false).await,
+        Some(Command::Reopen(args)) => run_close(&cfg, args, true).await,
+        Some(Command::Assign(args)) => run_assign(&cfg, args).await,
+        Some(Command::Label(args)) => run_label(&cfg, args).await,
+        Some(Command::Config(cmd)) => run_config(&cfg, cmd),
+        Some(Command::Notify(args)) => run_notify(&cfg, args).await,
+        Some(Command::Save(args)) => run_save(args),
+        Some(Command::Saved(cmd)) => run_saved(cmd),
+        Some(Command::Inbox(args)) => run_inbox(&cfg, args).await,
+        Some(Command::Auth(cmd)) => run_auth(&cfg, cmd).await,
+    }
+}
+
+async fn run_list(cfg: &Config) -> Result<(), AppErr> {
+    if cfg.explain() {
+        let filter: FilterConfig = cfg.into();
+        let queries = list::queries(&cfg.username(), &cfg.target()?, &filter);
+        return ui::print_queries(&queries, cfg.colors());
+    }
 
     let token: String = cfg.token()?;
     let targets: Vec<Target> = cfg.target()?;
     let user: Option<Username> = cfg.username();
-    log::debug!("Config: {:?}", &cfg);
+    log::debug!("Config: {:?}", cfg);
 
-    let filter: FilterConfig = (&cfg).into();
-    let display: DisplayConfig = (&cfg).into();
+    let filter: FilterConfig = cfg.into();
+    let display: DisplayConfig = cfg.into();
 
     let bounds: usize = cfg.limit() as usize * 2;
-    let (send, recv) = std::sync::mpsc::sync_channel::<Issue>(bounds);
-
     let rt = Runtime::new().unwrap();
-    rt.spawn(async move {
-        match list::list_issues(send, &user, &targets, &token, &filter).await {
-            Ok(_) => log::debug!("API requests completed"),
-            Err(e) => log::error!("{:?}", e),
+
+    let fetch = |cache: CacheMode| {
+        let (send, recv) = std::sync::mpsc::sync_channel::<Issue>(bounds);
+        let (user, targets, token, filter) =
+            (user.clone(), targets.clone(), token.clone(), filter.clone().with_cache(cache));
+        rt.spawn(async move {
+            match list::list_issues(send, &user, &targets, &token, &filter).await {
+                Ok(_) => log::debug!("API requests completed"),
+                Err(e) => log::error!("{:?}", e),
+            }
+        });
+        recv
+    };
+
+    // When refreshing in interactive or watch mode, wait for fresh results rather than showing
+    // the same cached results again
+    let mut mode: CacheMode = cfg.cache_mode();
+    let mut load = || {
+        let issues = ui::collect(fetch(mode), &display);
+        if mode == CacheMode::Default {
+            mode = CacheMode::Refresh;
         }
-    });
+        issues
+    };
 
-    ui::display(recv, display)?;
+    match (cfg.interactive(), cfg.watch()) {
+        (true, _) => interactive::browse(&display, load, |issue| {
+            let (token, issue) = (token.clone(), IssueRef::from(issue));
+            let details = rt.spawn(async move { show::get_issue(&token, &issue).await });
+            futures::executor::block_on(details).unwrap_or(Err(AppErr::ChannelError))
+        })?,
+        (false, Some(interval)) => watch::watch(interval, &display, load).await?,
+        (false, None) => ui::display(load()?, display)?,
+    }
+    cache::settle().await;
     rt.shutdown_background();
 
     Ok(())
 }
 
+async fn run_show(cfg: &Config, args: &ShowArgs) -> Result<(), AppErr> {
+    let token: String = cfg.token()?;
+    let issue = show::get_issue(&token, &args.issue).await?;
+    let display: DisplayConfig = cfg.into();
+    ui::print_details(&issue, &display);
+    Ok(())
+}
+
+async fn run_create(cfg: &Config, args: &CreateArgs) -> Result<(), AppErr> {
+    let token: String = cfg.token()?;
+    let (owner, name) = args.repository()?;
+    let draft: create::Draft = create::draft(args)?;
+    let issue = create::create_issue(&token, &owner, &name, &draft).await?;
+    println!("{}", issue.url);
+    Ok(())
+}
+
+async fn run_comment(cfg: &Config, args: &CommentArgs) -> Result<(), AppErr> {
+    let token: String = cfg.token()?;
+    let body: String = triage::message(&args.message)?;
+    let subject = triage::resolve(&token, &args.issue).await?;
+    triage::comment(&token, &subject, &body).await
+}
+
+async fn run_close(cfg: &Config, args: &StateArgs, reopen: bool) -> Result<(), AppErr> {
+    let token: String = cfg.token()?;
+    let body: Option<String> = match &args.message {
+        Some(_) => Some(triage::message(&args.message)?),
+        None => None,
+    };
+    let subject = triage::resolve(&token, &args.issue).await?;
+    if let Some(body) = body {
+        triage::comment(&token, &subject, &body).await?;
+    }
+    match reopen {
+        false => triage::close(&token, &subject).await,
+        true => triage::reopen(&token, &subject).await,
+    }
+}
+
+async fn run_assign(cfg: &Config, args: &AssignArgs) -> Result<(), AppErr> {
+    let token: String = cfg.token()?;
+    let users: Vec<String> = match args.users.is_empty() {
+        false => args.users.clone(),
+        true => vec![Username::from_token(&token).await?.0],
+    };
+    let subject = triage::resolve(&token, &args.issue).await?;
+    let user_ids: Vec<String> = repository::user_ids(&token, &users).await?;
+    triage::assign(&token, &subject, &user_ids, args.remove).await
+}
+
+async fn run_label(cfg: &Config, args: &LabelArgs) -> Result<(), AppErr> {
+    let token: String = cfg.token()?;
+    let issue = &args.issue;
+    let subject = triage::resolve(&token, issue).await?;
+    let repo = repository::get_repository(&token, &issue.owner, &issue.repo).await?;
+    let label_ids: Vec<String> = repo.label_ids(&token, &args.labels).await?;
+    triage::label(&token, &subject, &label_ids, args.remove).await
+}
+
+fn run_config(cfg: &Config, cmd: &ConfigCommand) -> Result<(), AppErr> {
+    match cmd {
+        ConfigCommand::Path => println!("{}", settings::config_file().display()),
+        ConfigCommand::Show => {
+            let settings: String = toml::to_string(cfg.settings())
+                .map_err(|e| AppErr::InvalidConfig(e.to_string()))?;
+            print!("{}", settings);
+        }
+    };
+    Ok(())
+}
+
+fn run_save(args: &SaveArgs) -> Result<(), AppErr> {
+    match saved::save(&args.name, &args.args)? {
+        true => println!("Replaced saved search '{}'", args.name),
+        false => println!("Saved search '{}'", args.name),
+    };
+    Ok(())
+}
+
+fn run_saved(cmd: &SavedCommand) -> Result<(), AppErr> {
+    match cmd {
+        SavedCommand::List => {
+            for (name, search) in saved::list()? {
+                println!("{}\t{}", name, saved::join(&search.args));
+            }
+        }
+        SavedCommand::Delete { name } => saved::delete(name)?,
+        SavedCommand::Edit { name } => {
+            let search: saved::Search = saved::edit(name)?;
+            println!("{}\t{}", name, saved::join(&search.args));
+        }
+    };
+    Ok(())
+}
+
+async fn run_notify(cfg: &Config, args: &NotifyArgs) -> Result<(), AppErr> {
+    let token: String = cfg.token()?;
+    let config: notify::NotifyConfig = cfg.notify(args)?;
+    notify::notify(&token, &config).await
+}
+
+async fn run_inbox(cfg: &Config, args: &InboxArgs) -> Result<(), AppErr> {
+    let token: String = cfg.token()?;
+    match &args.action {
+        None => {
+            let threads: Vec<inbox::Thread> = inbox::list(&token, &cfg.inbox(args)).await?;
+            ui::display_threads(&threads, cfg.colors());
+        }
+        Some(InboxCommand::Read { all: true, .. }) => inbox::mark_all_read(&token).await?,
+        Some(InboxCommand::Read { ids, .. }) => {
+            for id in ids {
+                inbox::mark_read(&token, *id).await?;
+            }
+        }
+        Some(InboxCommand::Unsubscribe { ids }) => {
+            for id in ids {
+                inbox::unsubscribe(&token, *id).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run_auth(cfg: &Config, cmd: &AuthCommand) -> Result<(), AppErr> {
+    let host: Host = cfg.host();
+    match cmd {
+        AuthCommand::Login(args) if args.web => {
+            let client_id: String = cfg.client_id(args)?;
+            let token: AccessToken = oauth::device_flow(&host, &client_id, &args.scopes).await?;
+            let missing: Vec<&String> =
+                args.scopes.iter().filter(|s| !token.scopes.contains(s)).collect();
+            if !missing.is_empty() {
+                log::warn!("Scopes {:?} were requested but not granted", missing);
+            }
+            let user: Username = Username::from_token(&token.token).await?;
+            let source: auth::Source = auth::store(&host, &token.token)?;
+            println!("Logged in to {} as {}, token stored in {}", host, user, source);
+        }
+        AuthCommand::Login(args) => {
+            let token: String = auth::read_token(&host, args.with_token)?;
+            let user: Username = Username::from_token(&token).await?;
+            let source: auth::Source = auth::store(&host, &token)?;
+            println!("Logged in to {} as {}, token stored in {}", host, user, source);
+        }
+        AuthCommand::Logout => match auth::delete(&host)? {
+            true => println!("Logged out of {}", host),
+            false => println!("No token stored for {}", host),
+        },
+        AuthCommand::Status => {
+            let (token, source) = cfg.token_source()?;
+            let user: User = user::api_lookup_username(&token).await?;
+            println!("Logged in to {} as {}, using token from {}", host, user.login, source);
+            if let Some(scopes) = api::scopes() {
+                println!("Token scopes: {}", scopes.join(", "));
+                for access in [Access::Read, Access::Write] {
+                    if let Some(scope) = auth::missing_scope(&scopes, access) {
+                        println!("Missing scope '{}' for {:?} access", scope, access);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub enum AppErr {
     MissingToken,
@@ -72,7 +315,26 @@ pub enum AppErr {
     NoTarget,
     InvalidTarget(String),
     ApiError,
-    Timeout,
     ChannelError,
     RateLimited,
+    OutputError,
+    InvalidConfig(String),
+    UnknownProfile(String),
+    NotFound(String),
+    EditorError,
+    InvalidInput(String),
+    IoError(String),
+    NotCached,
+    NoRemote,
+    InvalidRemote(String),
+    GitConfig(String),
+    AuthFailed(String),
+    MissingScope(String),
+}
+
+impl From<std::io::Error> for AppErr {
+    fn from(e: std::io::Error) -> Self {
+        log::error!("{}", e);
+        AppErr::IoError(e.to_string())
+    }
 }
@@ -0,0 +1,281 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    issue::{Issue, Root},
+    list::StateFilter,
+    qualifier::Qualifiers,
+    search::{SearchIssues, SearchQuery, Type},
+    sort::{Order, Property, Sorting},
+    watch::Interval,
+    AppErr, Target,
+};
+
+/// Maximum number of issues and pull requests that are checked for each reason when polling
+const POLL_LIMIT: u32 = 50;
+
+/// Why the user is notified about an issue or pull request
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Reason {
+    ReviewRequested,
+    Assigned,
+    Mentioned,
+}
+
+const REASONS: [Reason; 3] = [Reason::ReviewRequested, Reason::Assigned, Reason::Mentioned];
+
+pub struct NotifyConfig {
+    pub user: String,
+    pub targets: Vec<Target>,
+    pub interval: Interval,
+    pub command: Option<String>,
+    pub once: bool,
+}
+
+/// Items that have been seen in a previous poll, which are saved between runs so that the user is
+/// only notified once about each item
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct State {
+    seen: HashSet<String>,
+}
+
+/// Poll for review requests, assignments and mentions of the user, and send a notification for
+/// each one that was not seen in the previous poll. Nothing is sent on the first poll, when there
+/// is no saved state yet, since everything would be new.
+pub async fn notify(token: &str, cfg: &NotifyConfig) -> Result<(), AppErr> {
+    let path: PathBuf = state_file(&cfg.user);
+    let mut state: Option<State> = State::read(&path)?;
+
+    loop {
+        match poll(token, cfg).await {
+            Ok(events) => {
+                let seen: HashSet<String> = events.iter().map(|(r, i)| key(*r, i)).collect();
+                match &state {
+                    Some(state) => events
+                        .iter()
+                        .filter(|(reason, issue)| !state.seen.contains(&key(*reason, issue)))
+                        .for_each(|(reason, issue)| send(*reason, issue, &cfg.command)),
+                    None => log::info!("First run, {} items will not be notified", seen.len()),
+                }
+                let current = State { seen };
+                current.write(&path)?;
+                state = Some(current);
+            }
+            Err(e) => log::error!("Unable to poll for notifications: {:?}", e),
+        }
+
+        if cfg.once {
+            return Ok(());
+        }
+        tokio::time::sleep(cfg.interval.into()).await;
+    }
+}
+
+async fn poll(token: &str, cfg: &NotifyConfig) -> Result<Vec<(Reason, Issue)>, AppErr> {
+    let mut events: Vec<(Reason, Issue)> = Vec::new();
+    for reason in REASONS {
+        let query = reason.query(&cfg.user, &cfg.targets).build();
+        let root: Root = crate::api::v4::request(token, query).await?;
+        events.extend(root.data.search.edges.into_iter().map(|n| (reason, n.node)));
+    }
+    Ok(events)
+}
+
+impl Reason {
+    fn query(&self, user: &str, targets: &[Target]) -> SearchIssues {
+        let user: Option<String> = Some(user.to_string());
+        SearchIssues {
+            state: StateFilter::Open,
+            assignee: match self {
+                Reason::Assigned => user.clone(),
+                _ => None,
+            },
+            review_requested: match self {
+                Reason::ReviewRequested => user.clone(),
+                _ => None,
+            },
+            archived: false,
+            labels: Vec::with_capacity(0),
+            project: None,
+            resource_type: match self {
+                Reason::ReviewRequested => Some(Type::ReviewRequest),
+                _ => None,
+            },
+            targets: targets.to_vec(),
+            qualifiers: Qualifiers {
+                mentions: match self {
+                    Reason::Mentioned => user,
+                    _ => None,
+                },
+                ..Qualifiers::default()
+            },
+            sort: Sorting(Property::Updated, Order::Descending),
+            search: None,
+            query: None,
+            limit: POLL_LIMIT,
+            cursor: None,
+        }
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason: &str = match self {
+            Reason::ReviewRequested => "review_requested",
+            Reason::Assigned => "assigned",
+            Reason::Mentioned => "mentioned",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+fn key(reason: Reason, issue: &Issue) -> String {
+    format!("{}:{}", reason, issue.id)
+}
+
+/// Print the notification, and send it either with `command` if given, or else as a desktop
+/// notification. Details of the notification are available to `command` as environment variables.
+fn send(reason: Reason, issue: &Issue, command: &Option<String>) {
+    let summary: String = format!(
+        "{}: {}#{}",
+        reason, issue.repository.name_with_owner, issue.number
+    );
+    println!("{} {}", summary, issue.title);
+
+    let mut cmd: Command = match command {
+        Some(command) => shell(command),
+        None => match desktop_notification(&summary, &issue.title) {
+            Some(cmd) => cmd,
+            None => return,
+        },
+    };
+
+    let status = cmd
+        .env("GISS_REASON", reason.to_string())
+        .env("GISS_REPO", &issue.repository.name_with_owner)
+        .env("GISS_NUMBER", issue.number.to_string())
+        .env("GISS_TITLE", &issue.title)
+        .env("GISS_URL", &issue.url)
+        .stdin(Stdio::null())
+        .status();
+
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => log::warn!("Notification command failed with {}", status),
+        Err(e) => log::warn!("Unable to send notification: {}", e),
+    }
+}
+
+/// Run `command` with the shell of the platform, which is `sh` except on Windows
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+/// The command is passed to `cmd` as is, since `cmd` does not parse quoted arguments like other
+/// programs on Windows do
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").raw_arg(command);
+    cmd
+}
+
+/// Command for a desktop notification, which is sent with `osascript` on macOS and `notify-send`
+/// on other Unix systems. Windows has no such command, so notifications are only printed there.
+#[cfg(target_os = "macos")]
+fn desktop_notification(summary: &str, body: &str) -> Option<Command> {
+    let mut cmd = Command::new("osascript");
+    cmd.args(["-e", "on run argv", "-e"])
+        .arg("display notification (item 2 of argv) with title \"giss\" subtitle (item 1 of argv)")
+        .args(["-e", "end run", summary, body]);
+    Some(cmd)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn desktop_notification(summary: &str, body: &str) -> Option<Command> {
+    let mut cmd = Command::new("notify-send");
+    cmd.args(["--app-name=giss", summary, body]);
+    Some(cmd)
+}
+
+#[cfg(not(unix))]
+fn desktop_notification(_summary: &str, _body: &str) -> Option<Command> {
+    None
+}
+
+/// File for the state of the user on the current host
+fn state_file(user: &str) -> PathBuf {
+    let host: String = crate::api::host().to_string();
+    crate::settings::config_dir()
+        .join("notify")
+        .join(format!("{}@{}.json", user, host))
+}
+
+impl State {
+    /// Read the saved state, if any. An invalid state file is ignored, which means that nothing is
+    /// notified until the state is saved again.
+    fn read(path: &Path) -> Result<Option<State>, AppErr> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => match serde_json::from_str(&content) {
+                Ok(state) => Ok(Some(state)),
+                Err(e) => {
+                    log::warn!("Ignoring invalid state in {:?}, starting over: {}", path, e);
+                    Ok(None)
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write to a temporary file first, which replaces the previous state once it is complete
+    fn write(&self, path: &Path) -> Result<(), AppErr> {
+        let dir: &Path = path.parent().unwrap_or_else(|| Path::new("."));
+        std::fs::create_dir_all(dir)?;
+        let file = tempfile::NamedTempFile::new_in(dir)?;
+        serde_json::to_writer(&file, self)?;
+        file.persist(path).map_err(|e| e.error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_is_saved_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path: PathBuf = dir.path().join("notify").join("octocat@github.com.json");
+        assert!(State::read(&path).unwrap().is_none());
+
+        let seen: HashSet<String> =
+            ["assigned:1", "mentioned:2"].iter().map(|s| s.to_string()).collect();
+        State { seen: seen.clone() }.write(&path).unwrap();
+        assert_eq!(seen, State::read(&path).unwrap().unwrap().seen);
+
+        State::default().write(&path).unwrap();
+        assert!(State::read(&path).unwrap().unwrap().seen.is_empty());
+        assert_eq!(1, std::fs::read_dir(path.parent().unwrap()).unwrap().count());
+    }
+
+    #[test]
+    fn invalid_state_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path: PathBuf = dir.path().join("state.json");
+        for content in ["", "{\"seen\": [", "[]"] {
+            std::fs::write(&path, content).unwrap();
+            assert!(State::read(&path).unwrap().is_none(), "{:?}", content);
+        }
+    }
+}
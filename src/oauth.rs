@@ -0,0 +1,123 @@
+use crate::{
+    api::{Host, CLIENT},
+    AppErr,
+};
+use serde::Deserialize;
+use std::{
+    io::Write,
+    time::{Duration, Instant},
+};
+
+const GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+#[derive(Debug, Deserialize)]
+struct DeviceCode {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    expires_in: u64,
+    interval: u64,
+}
+
+/// Response when polling for an access token, which is either the token or an error such as
+/// `authorization_pending`, while the user has not yet entered the code
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    scope: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+    interval: Option<u64>,
+}
+
+/// A token obtained through the OAuth device flow, and the scopes that were granted
+#[derive(Debug)]
+pub struct AccessToken {
+    pub token: String,
+    pub scopes: Vec<String>,
+}
+
+/// Authenticate with GitHub's OAuth device flow, where the user enters a code in the browser to
+/// grant the OAuth app `client_id` access with `scopes`.
+pub async fn device_flow(
+    host: &Host,
+    client_id: &str,
+    scopes: &[String],
+) -> Result<AccessToken, AppErr> {
+    let scope: String = scopes.join(" ");
+    let code: DeviceCode = post(
+        &format!("{}/login/device/code", host.web_url()),
+        &[("client_id", client_id), ("scope", &scope)],
+    )
+    .await?;
+
+    let mut stderr = std::io::stderr();
+    writeln!(stderr, "Enter code {} at {}", code.user_code, code.verification_uri)?;
+    if let Err(e) = crate::browser::open(&code.verification_uri) {
+        log::debug!("Unable to open browser: {:?}", e);
+    }
+
+    let url: String = format!("{}/login/oauth/access_token", host.web_url());
+    let expires: Instant = Instant::now() + Duration::from_secs(code.expires_in);
+    let mut interval: Duration = Duration::from_secs(code.interval);
+
+    while Instant::now() < expires {
+        tokio::time::sleep(interval).await;
+        let form = [
+            ("client_id", client_id),
+            ("device_code", &code.device_code),
+            ("grant_type", GRANT_TYPE),
+        ];
+        let response: TokenResponse = post(&url, &form).await?;
+        match (response.access_token, response.error.as_deref()) {
+            (Some(token), _) => {
+                let scopes: Vec<String> = response
+                    .scope
+                    .unwrap_or_default()
+                    .split([',', ' '])
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.to_string())
+                    .collect();
+                return Ok(AccessToken { token, scopes });
+            }
+            (None, Some("authorization_pending")) => continue,
+            (None, Some("slow_down")) => {
+                interval = match response.interval {
+                    Some(seconds) => Duration::from_secs(seconds),
+                    None => interval + Duration::from_secs(5),
+                };
+            }
+            (None, error) => {
+                let reason: String = response
+                    .error_description
+                    .or_else(|| error.map(|e| e.to_string()))
+                    .unwrap_or_else(|| String::from("no token in response"));
+                return Err(AppErr::AuthFailed(reason));
+            }
+        }
+    }
+
+    Err(AppErr::AuthFailed(String::from("The code expired before it was entered")))
+}
+
+async fn post<T: serde::de::DeserializeOwned>(
+    url: &str,
+    form: &[(&str, &str)],
+) -> Result<T, AppErr> {
+    let response: reqwest::Response = CLIENT
+        .post(url)
+        .header("Accept", "application/json")
+        .header("User-Agent", "giss")
+        .form(form)
+        .send()
+        .await?;
+
+    match response.status().as_u16() {
+        200 => Ok(response.json::<T>().await?),
+        code => {
+            let error: String = response.text().await?;
+            log::error!("GitHub: {} - {}", code, error);
+            Err(AppErr::AuthFailed(format!("Request to {} failed with status {}", url, code)))
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use std::{fmt, io::Write, str::FromStr};
+
+use serde::Serialize;
+
+use crate::{
+    github_resources::ghrs,
+    issue::{Issue, Label, UserFields},
+    search::Type,
+    AppErr,
+};
+
+/// Format used when printing issues and pull requests
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum OutputFormat {
+    /// Human readable, and possibly colored, text
+    #[default]
+    Text,
+    /// All issues in a single JSON array
+    Json,
+    /// One JSON object per line
+    Ndjson,
+    /// Comma separated values, with a header row
+    Csv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" | "jsonl" => Ok(OutputFormat::Ndjson),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(format!("Unrecognized output format '{}'", s)),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// Format to write records in, or `None` for text, which is printed by the `ui` module
+    pub fn records(&self) -> Option<RecordFormat> {
+        match self {
+            OutputFormat::Text => None,
+            OutputFormat::Json => Some(RecordFormat::Json),
+            OutputFormat::Ndjson => Some(RecordFormat::Ndjson),
+            OutputFormat::Csv => Some(RecordFormat::Csv),
+        }
+    }
+}
+
+/// Machine readable formats, which records can be written in
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RecordFormat {
+    Json,
+    Ndjson,
+    Csv,
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let output: &str = match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Csv => "csv",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+/// Flat representation of an issue or pull request, intended for consumption by other tools
+#[derive(Debug, Serialize)]
+pub struct IssueRecord {
+    pub id: u64,
+    pub number: u32,
+    pub title: String,
+    pub state: ghrs::State,
+    // Only set for issues, the column is empty for pull requests in CSV output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_reason: Option<ghrs::StateReason>,
+    pub kind: String,
+    pub repository: String,
+    pub assignees: Vec<String>,
+    pub labels: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub link: String,
+    pub comments: u32,
+    pub reactions: u32,
+    // Only set for pull requests, the columns are empty for issues in CSV output
+    #[serde(flatten)]
+    pub pull_request: Option<PullRequestRecord>,
+}
+
+/// Review status, checks and mergeability of a pull request
+#[derive(Debug, Serialize)]
+pub struct PullRequestRecord {
+    pub draft: bool,
+    pub review_decision: Option<String>,
+    pub checks: Option<String>,
+    pub mergeable: Option<String>,
+    pub additions: u32,
+    pub deletions: u32,
+    pub base_branch: String,
+    pub head_branch: String,
+}
+
+impl PullRequestRecord {
+    fn csv_row(&self) -> Vec<String> {
+        vec![
+            self.draft.to_string(),
+            self.review_decision.clone().unwrap_or_default(),
+            self.checks.clone().unwrap_or_default(),
+            self.mergeable.clone().unwrap_or_default(),
+            self.additions.to_string(),
+            self.deletions.to_string(),
+            self.base_branch.clone(),
+            self.head_branch.clone(),
+        ]
+    }
+
+    fn new(issue: &Issue) -> Option<PullRequestRecord> {
+        Some(PullRequestRecord {
+            draft: issue.is_draft?,
+            review_decision: issue.review_decision.clone(),
+            checks: issue.checks().map(|checks| checks.to_string()),
+            mergeable: issue.mergeable.clone(),
+            additions: issue.additions?,
+            deletions: issue.deletions?,
+            base_branch: issue.base_branch.clone()?,
+            head_branch: issue.head_branch.clone()?,
+        })
+    }
+}
+
+const CSV_HEADER: [&str; 22] = [
+    "id",
+    "number",
+    "title",
+    "state",
+    "kind",
+    "repository",
+    "assignees",
+    "labels",
+    "created_at",
+    "updated_at",
+    "link",
+    "comments",
+    "reactions",
+    "draft",
+    "review_decision",
+    "checks",
+    "mergeable",
+    "additions",
+    "deletions",
+    "base_branch",
+    "head_branch",
+    "state_reason",
+];
+
+impl IssueRecord {
+    pub fn new(issue: &Issue, kind: Type) -> IssueRecord {
+        let kind: &str = match kind {
+            Type::Issue => "issue",
+            Type::PullRequest => "pull_request",
+            Type::ReviewRequest => "review_request",
+        };
+
+        IssueRecord {
+            id: issue.id,
+            number: issue.number,
+            title: issue.title.clone(),
+            state: issue.state,
+            state_reason: issue.state_reason,
+            kind: kind.to_string(),
+            repository: issue.repository.name_with_owner.clone(),
+            assignees: issue.assignees.nodes.iter().map(|a: &UserFields| a.login.clone()).collect(),
+            labels: issue.labels.nodes.iter().map(|l: &Label| l.name.clone()).collect(),
+            created_at: issue.created_at.clone(),
+            updated_at: issue.updated_at.clone(),
+            link: issue.link(),
+            comments: issue.comments.total_count,
+            reactions: issue.reactions.total_count,
+            pull_request: PullRequestRecord::new(issue),
+        }
+    }
+
+    fn csv_row(&self) -> Vec<String> {
+        let mut row: Vec<String> = vec![
+            self.id.to_string(),
+            self.number.to_string(),
+            self.title.clone(),
+            self.state.to_string(),
+            self.kind.clone(),
+            self.repository.clone(),
+            self.assignees.join(","),
+            self.labels.join(","),
+            self.created_at.clone(),
+            self.updated_at.clone(),
+            self.link.clone(),
+            self.comments.to_string(),
+            self.reactions.to_string(),
+        ];
+        match &self.pull_request {
+            Some(pr) => row.extend(pr.csv_row()),
+            None => row.extend(std::iter::repeat_n(String::new(), 8)),
+        }
+        let state_reason: Option<serde_json::Value> =
+            self.state_reason.and_then(|reason| serde_json::to_value(reason).ok());
+        row.push(state_reason.as_ref().and_then(|r| r.as_str()).unwrap_or_default().to_string());
+        row
+    }
+}
+
+/// Write all records to `out` in the given format
+pub fn write_records<W: Write>(
+    out: &mut W,
+    records: &[IssueRecord],
+    format: RecordFormat,
+) -> Result<(), AppErr> {
+    match format {
+        RecordFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, records)?;
+            writeln!(out)?;
+        }
+        RecordFormat::Ndjson => {
+            for record in records {
+                serde_json::to_writer(&mut *out, record)?;
+                writeln!(out)?;
+            }
+        }
+        RecordFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            writer.write_record(CSV_HEADER)?;
+            for record in records {
+                writer.write_record(record.csv_row())?;
+            }
+            writer.flush()?;
+        }
+    };
+    Ok(())
+}
+
+impl From<serde_json::Error> for AppErr {
+    fn from(e: serde_json::Error) -> Self {
+        log::error!("Unable to serialize output: {}", e);
+        AppErr::OutputError
+    }
+}
+
+impl From<csv::Error> for AppErr {
+    fn from(e: csv::Error) -> Self {
+        log::error!("Unable to write CSV output: {}", e);
+        AppErr::OutputError
+    }
+}
@@ -61,3 +61,30 @@ impl Display for Project {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(s: &str) -> Result<String, String> {
+        Project::from_str(s).map(|project| project.to_string())
+    }
+
+    #[test]
+    fn owner_projects() {
+        assert_eq!(Ok(String::from("mantono/1")), project("mantono/1"));
+    }
+
+    #[test]
+    fn repository_projects() {
+        assert_eq!(Ok(String::from("mantono/giss/2")), project("mantono/giss/2"));
+    }
+
+    #[test]
+    fn invalid_projects() {
+        assert!(project("mantono").is_err());
+        assert!(project("mantono/giss/2/3").is_err());
+        assert_eq!(Err(String::from("id must be a number")), project("mantono/giss"));
+        assert_eq!(Err(String::from("id must be a number")), project("mantono/giss/x"));
+    }
+}
@@ -0,0 +1,527 @@
+use std::{
+    fmt,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::github_resources::ghrs::StateReason;
+
+/// Search qualifiers that narrow down a search for issues and pull requests, in addition to state,
+/// type, labels and targets. These are described in GitHub's documentation on searching issues
+/// and pull requests.
+#[derive(Debug, Clone, Default)]
+pub struct Qualifiers {
+    pub author: Option<String>,
+    pub mentions: Option<String>,
+    pub commenter: Option<String>,
+    pub involves: Option<String>,
+    pub reviewed_by: Option<String>,
+    pub milestone: Option<String>,
+    pub missing: Vec<Missing>,
+    pub exclude_labels: Vec<String>,
+    pub created: Option<DateFilter>,
+    pub updated: Option<DateFilter>,
+    pub language: Option<String>,
+    pub draft: Option<bool>,
+    pub review: Option<Review>,
+    pub status: Option<Status>,
+    pub merged: bool,
+    pub state_reason: Option<StateReason>,
+}
+
+impl Qualifiers {
+    /// Whether any of the qualifiers only applies to pull requests, in which case no issues can
+    /// match the search
+    pub fn pull_requests_only(&self) -> bool {
+        self.reviewed_by.is_some()
+            || self.draft.is_some()
+            || self.review.is_some()
+            || self.status.is_some()
+            || self.merged
+    }
+
+    /// Whether any of the qualifiers only applies to issues, in which case no pull requests can
+    /// match the search
+    pub fn issues_only(&self) -> bool {
+        self.state_reason.is_some()
+    }
+
+    /// Whether the qualifiers only match issues and pull requests that are closed
+    pub fn closed_only(&self) -> bool {
+        self.merged || self.state_reason.map(|r| r.is_closed()).unwrap_or(false)
+    }
+}
+
+impl fmt::Display for Qualifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let user = |name: &str, user: &Option<String>| -> Option<String> {
+            user.as_ref().map(|u| format!("{}:{}", name, u))
+        };
+        let parts: Vec<String> = vec![
+            user("author", &self.author),
+            user("mentions", &self.mentions),
+            user("commenter", &self.commenter),
+            user("involves", &self.involves),
+            user("reviewed-by", &self.reviewed_by),
+            self.milestone.as_ref().map(|m| format!("milestone:{}", quote(m))),
+            self.created.as_ref().map(|d| format!("created:{}", d)),
+            self.updated.as_ref().map(|d| format!("updated:{}", d)),
+            self.language.as_ref().map(|l| format!("language:{}", quote(l))),
+            self.draft.map(|draft| format!("draft:{}", draft)),
+            self.review.map(|r| format!("review:{}", r)),
+            self.status.map(|s| format!("status:{}", s)),
+            match self.merged {
+                true => Some(String::from("is:merged")),
+                false => None,
+            },
+            self.state_reason.map(|r| format!("reason:{}", quote(&r.to_string()))),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(self.missing.iter().map(|m| format!("no:{}", m)))
+        .chain(self.exclude_labels.iter().map(|l| format!("-label:{}", quote(l))))
+        .collect();
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// Quote a value that contains whitespace, so it is not split into several search terms
+pub fn quote(value: &str) -> String {
+    match value.contains(char::is_whitespace) {
+        true => format!("\"{}\"", value.replace('"', "")),
+        false => value.to_string(),
+    }
+}
+
+/// A field that an issue or pull request does not have any value for
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Missing {
+    Assignee,
+    Label,
+    Milestone,
+    Project,
+}
+
+impl FromStr for Missing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "assignee" => Ok(Missing::Assignee),
+            "label" => Ok(Missing::Label),
+            "milestone" => Ok(Missing::Milestone),
+            "project" => Ok(Missing::Project),
+            _ => Err(format!(
+                "Invalid field '{}', expected assignee, label, milestone or project",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Missing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field: &str = match self {
+            Missing::Assignee => "assignee",
+            Missing::Label => "label",
+            Missing::Milestone => "milestone",
+            Missing::Project => "project",
+        };
+        write!(f, "{}", field)
+    }
+}
+
+/// Review status of a pull request
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Review {
+    None,
+    Required,
+    Approved,
+    ChangesRequested,
+}
+
+impl FromStr for Review {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "none" => Ok(Review::None),
+            "required" => Ok(Review::Required),
+            "approved" => Ok(Review::Approved),
+            "changes_requested" => Ok(Review::ChangesRequested),
+            _ => Err(format!(
+                "Invalid review '{}', expected none, required, approved or changes_requested",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Review {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let review: &str = match self {
+            Review::None => "none",
+            Review::Required => "required",
+            Review::Approved => "approved",
+            Review::ChangesRequested => "changes_requested",
+        };
+        write!(f, "{}", review)
+    }
+}
+
+/// Combined status of the commit checks of a pull request
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pending,
+    Success,
+    Failure,
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pending" => Ok(Status::Pending),
+            "success" => Ok(Status::Success),
+            "failure" => Ok(Status::Failure),
+            _ => Err(format!("Invalid status '{}', expected pending, success or failure", s)),
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status: &str = match self {
+            Status::Pending => "pending",
+            Status::Success => "success",
+            Status::Failure => "failure",
+        };
+        write!(f, "{}", status)
+    }
+}
+
+lazy_static! {
+    static ref DATE: Regex =
+        Regex::new(r"^\d{4}-\d{2}-\d{2}(T\d{2}:\d{2}(:\d{2})?(Z|[+-]\d{2}:\d{2})?)?$").unwrap();
+}
+
+/// A date, or date and time, in ISO 8601 format (such as `2021-04-30` or `2021-04-30T12:00:00Z`)
+/// compared with a date of an issue or pull request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateFilter {
+    On(String),
+    After(String),
+    OnOrAfter(String),
+    Before(String),
+    OnOrBefore(String),
+    Between(String, String),
+}
+
+impl DateFilter {
+    /// Filter for dates from `from` until `to`, either of which may be left out. When only `to` is
+    /// given, it is only included if `inclusive` is set, while a range always includes both dates.
+    pub fn range(from: &Option<When>, to: &Option<When>, inclusive: bool) -> Option<DateFilter> {
+        match (from, to) {
+            (Some(from), Some(to)) => Some(DateFilter::Between(from.resolve(), to.resolve())),
+            (Some(from), None) => Some(DateFilter::OnOrAfter(from.resolve())),
+            (None, Some(to)) if inclusive => Some(DateFilter::OnOrBefore(to.resolve())),
+            (None, Some(to)) => Some(DateFilter::Before(to.resolve())),
+            (None, None) => None,
+        }
+    }
+}
+
+impl FromStr for DateFilter {
+    type Err = String;
+
+    /// Parse a date with an optional comparison, such as `>2021-04-30`, `<=2021-04-30` or
+    /// `2021-01-01..2021-04-30`, in the same format as GitHub's search syntax
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s: &str = s.trim();
+        let date = |date: &str| -> Result<String, String> {
+            match DATE.is_match(date) {
+                true => Ok(date.to_string()),
+                false => Err(format!("Invalid date '{}', expected a date like 2021-04-30", date)),
+            }
+        };
+        if let Some((from, to)) = s.split_once("..") {
+            return Ok(DateFilter::Between(date(from)?, date(to)?));
+        }
+        let filter: DateFilter = match s {
+            s if s.starts_with(">=") => DateFilter::OnOrAfter(date(&s[2..])?),
+            s if s.starts_with("<=") => DateFilter::OnOrBefore(date(&s[2..])?),
+            s if s.starts_with('>') => DateFilter::After(date(&s[1..])?),
+            s if s.starts_with('<') => DateFilter::Before(date(&s[1..])?),
+            s => DateFilter::On(date(s)?),
+        };
+        Ok(filter)
+    }
+}
+
+impl fmt::Display for DateFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DateFilter::On(date) => write!(f, "{}", date),
+            DateFilter::After(date) => write!(f, ">{}", date),
+            DateFilter::OnOrAfter(date) => write!(f, ">={}", date),
+            DateFilter::Before(date) => write!(f, "<{}", date),
+            DateFilter::OnOrBefore(date) => write!(f, "<={}", date),
+            DateFilter::Between(from, to) => write!(f, "{}..{}", from, to),
+        }
+    }
+}
+
+/// A point in time given either as a date (or date and time) in ISO 8601 format, or as a duration
+/// relative to now, such as `12h`, `3d`, `2w`, `1m` or `1y`, where `m` is months and not minutes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum When {
+    Date(String),
+    Ago(u32, Unit),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Unit {
+    Hours,
+    Days,
+    Weeks,
+    Months,
+    Years,
+}
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+impl FromStr for When {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s: &str = s.trim();
+        if DATE.is_match(s) {
+            return Ok(When::Date(s.to_string()));
+        }
+        let invalid = || format!("Invalid date or duration '{}', expected '2021-04-30' or '2w'", s);
+        let split: usize = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+        let amount: u32 = s[..split].parse().map_err(|_| invalid())?;
+        let unit: Unit = match &s[split..] {
+            "h" => Unit::Hours,
+            "d" => Unit::Days,
+            "w" => Unit::Weeks,
+            "m" => Unit::Months,
+            "y" => Unit::Years,
+            _ => return Err(invalid()),
+        };
+        Ok(When::Ago(amount, unit))
+    }
+}
+
+impl When {
+    /// Resolve to a date that can be used in a search, which also has a time of day when the
+    /// duration is given in hours
+    pub fn resolve(&self) -> String {
+        let now: u64 = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.resolve_at(now)
+    }
+
+    /// Resolve relative to `now`, given in seconds since the Unix epoch (in UTC). Like times in
+    /// hours, dates are never earlier than the epoch.
+    fn resolve_at(&self, now: u64) -> String {
+        let today: i64 = (now / SECONDS_PER_DAY) as i64;
+        let date: (i64, u32, u32) = match *self {
+            When::Date(ref date) => return date.clone(),
+            When::Ago(hours, Unit::Hours) => {
+                let time: u64 = now.saturating_sub(hours as u64 * 3600);
+                let (year, month, day) = civil_from_days((time / SECONDS_PER_DAY) as i64);
+                let seconds: u64 = time % SECONDS_PER_DAY;
+                return format!(
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+                    year,
+                    month,
+                    day,
+                    seconds / 3600,
+                    seconds % 3600 / 60,
+                    seconds % 60
+                );
+            }
+            When::Ago(days, Unit::Days) => civil_from_days(today - days as i64),
+            When::Ago(weeks, Unit::Weeks) => civil_from_days(today - 7 * weeks as i64),
+            When::Ago(months, Unit::Months) => {
+                months_before(civil_from_days(today), months as i64)
+            }
+            When::Ago(years, Unit::Years) => {
+                months_before(civil_from_days(today), 12 * years as i64)
+            }
+        };
+        let (year, month, day) = date.max(civil_from_days(0));
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+/// Convert a number of days since the Unix epoch to a date in the proleptic Gregorian calendar,
+/// with Howard Hinnant's `civil_from_days` algorithm
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z: i64 = days + 719_468;
+    let era: i64 = z.div_euclid(146_097);
+    let day_of_era: i64 = z.rem_euclid(146_097);
+    let year_of_era: i64 =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year: i64 = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp: i64 = (5 * day_of_year + 2) / 153;
+    let day: u32 = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month: u32 = match mp < 10 {
+        true => mp + 3,
+        false => mp - 9,
+    } as u32;
+    let year: i64 = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// The same day of the month, a number of months before the given date. The day is moved back to
+/// the last day of the month if the month is shorter, such as one month before March 31st.
+fn months_before((year, month, day): (i64, u32, u32), months: i64) -> (i64, u32, u32) {
+    let total: i64 = year * 12 + (month as i64 - 1) - months;
+    let (year, month): (i64, u32) = (total.div_euclid(12), total.rem_euclid(12) as u32 + 1);
+    (year, month, day.min(days_in_month(year, month)))
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    let leap: bool = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-03-31T12:30:15Z
+    const END_OF_MARCH: u64 = 1_711_888_215;
+    /// 2023-03-31T00:00:00Z
+    const END_OF_MARCH_2023: u64 = 1_680_220_800;
+    /// 2024-02-29T08:00:00Z
+    const LEAP_DAY: u64 = 1_709_193_600;
+    /// 2021-01-05T01:00:00Z
+    const NEW_YEAR: u64 = 1_609_808_400;
+
+    fn resolve(when: &str, now: u64) -> String {
+        When::from_str(when).unwrap().resolve_at(now)
+    }
+
+    #[test]
+    fn hours_are_resolved_to_time_of_day() {
+        assert_eq!("2024-03-31T09:30:15Z", resolve("3h", END_OF_MARCH));
+        assert_eq!("2024-03-30T23:30:15Z", resolve("13h", END_OF_MARCH));
+        assert_eq!("2021-01-04T23:00:00Z", resolve("2h", NEW_YEAR));
+    }
+
+    #[test]
+    fn days_and_weeks_cross_years() {
+        assert_eq!("2020-12-31", resolve("5d", NEW_YEAR));
+        assert_eq!("2020-12-29", resolve("1w", NEW_YEAR));
+        assert_eq!("2024-03-03", resolve("4w", END_OF_MARCH));
+    }
+
+    #[test]
+    fn months_are_clamped_to_end_of_month() {
+        assert_eq!("2024-02-29", resolve("1m", END_OF_MARCH));
+        assert_eq!("2023-02-28", resolve("1m", END_OF_MARCH_2023));
+        assert_eq!("2024-01-31", resolve("2m", END_OF_MARCH));
+        assert_eq!("2023-02-28", resolve("13m", END_OF_MARCH));
+    }
+
+    #[test]
+    fn years_from_leap_day() {
+        assert_eq!("2023-02-28", resolve("1y", LEAP_DAY));
+        assert_eq!("2020-02-29", resolve("4y", LEAP_DAY));
+        assert_eq!("2016-02-29", resolve("8y", LEAP_DAY));
+    }
+
+    #[test]
+    fn durations_are_not_resolved_before_epoch() {
+        let max: u32 = u32::MAX;
+        assert_eq!("1970-01-01", When::Ago(max, Unit::Years).resolve_at(END_OF_MARCH));
+        assert_eq!("1970-01-01", When::Ago(max, Unit::Months).resolve_at(END_OF_MARCH));
+        assert_eq!("1970-01-01", When::Ago(max, Unit::Weeks).resolve_at(END_OF_MARCH));
+        assert_eq!("1970-01-01", resolve("100y", END_OF_MARCH));
+        assert_eq!("1970-01-01T00:00:00Z", When::Ago(max, Unit::Hours).resolve_at(END_OF_MARCH));
+    }
+
+    #[test]
+    fn missing_fields_round_trip() {
+        for missing in [Missing::Assignee, Missing::Label, Missing::Milestone, Missing::Project] {
+            assert_eq!(Ok(missing), Missing::from_str(&missing.to_string()));
+        }
+        assert_eq!(Ok(Missing::Label), Missing::from_str("Label"));
+        assert!(Missing::from_str("reviewer").unwrap_err().contains("'reviewer'"));
+        assert!(Missing::from_str("").is_err());
+    }
+
+    #[test]
+    fn reviews_round_trip() {
+        let reviews = [Review::None, Review::Required, Review::Approved, Review::ChangesRequested];
+        for review in reviews {
+            assert_eq!(Ok(review), Review::from_str(&review.to_string()));
+        }
+        assert_eq!(Ok(Review::ChangesRequested), Review::from_str("Changes-Requested"));
+        assert!(Review::from_str("rejected").unwrap_err().contains("'rejected'"));
+    }
+
+    #[test]
+    fn statuses_round_trip() {
+        for status in [Status::Pending, Status::Success, Status::Failure] {
+            assert_eq!(Ok(status), Status::from_str(&status.to_string()));
+        }
+        assert_eq!(Ok(Status::Failure), Status::from_str("FAILURE"));
+        assert!(Status::from_str("error").unwrap_err().contains("'error'"));
+    }
+
+    #[test]
+    fn date_filters_round_trip() {
+        let filters = [
+            "2021-04-30",
+            ">2021-04-30",
+            ">=2021-04-30T12:00:00Z",
+            "<2021-04-30T12:00+02:00",
+            "<=2021-04-30",
+            "2021-01-01..2021-04-30",
+        ];
+        for filter in filters {
+            assert_eq!(filter, DateFilter::from_str(filter).unwrap().to_string());
+        }
+        let between = DateFilter::Between(String::from("2021-01-01"), String::from("2021-04-30"));
+        assert_eq!(Ok(between), DateFilter::from_str(" 2021-01-01..2021-04-30 "));
+    }
+
+    #[test]
+    fn invalid_date_filters_are_rejected() {
+        let filters = ["", ">", "=>2021-04-30", "2021-4-30", "yesterday", "2021-01-01.."];
+        for filter in filters {
+            assert!(DateFilter::from_str(filter).is_err(), "{:?}", filter);
+        }
+        let error: String = DateFilter::from_str(">=30/04/2021").unwrap_err();
+        assert_eq!("Invalid date '30/04/2021', expected a date like 2021-04-30", error);
+    }
+
+    #[test]
+    fn invalid_durations_are_rejected() {
+        for when in ["", "2", "h", "2x", "-2d", "2 d", "4294967296d"] {
+            assert!(When::from_str(when).is_err(), "{:?}", when);
+        }
+    }
+
+    #[test]
+    fn dates_are_kept_as_given() {
+        assert_eq!("2021-04-30", resolve("2021-04-30", END_OF_MARCH));
+        assert_eq!("2021-04-30T12:00:00Z", resolve("2021-04-30T12:00:00Z", END_OF_MARCH));
+    }
+}
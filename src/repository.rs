@@ -0,0 +1,172 @@
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{search::GraphQLQuery, AppErr};
+
+/// Metadata about a repository, needed when issues or pull requests are modified, since GitHub's
+/// API refers to the repository, labels and milestones by their node id rather than their name.
+#[derive(Debug, Deserialize)]
+pub struct RepositoryMeta {
+    pub id: String,
+    #[serde(skip)]
+    owner: String,
+    #[serde(skip)]
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LabelMeta {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MilestoneNode {
+    nodes: Vec<Milestone>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Milestone {
+    id: String,
+    title: String,
+    state: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Root<T> {
+    data: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepositoryData<T> {
+    repository: Option<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LabelData {
+    label: Option<LabelMeta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MilestoneData {
+    milestone: Option<Milestone>,
+    milestones: Option<MilestoneNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserData {
+    user: Option<UserId>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserId {
+    id: String,
+}
+
+impl RepositoryMeta {
+    /// Resolve the id of each label, which is looked up by name without regard to case
+    pub async fn label_ids(&self, token: &str, names: &[String]) -> Result<Vec<String>, AppErr> {
+        let mut ids: Vec<String> = Vec::with_capacity(names.len());
+        for name in names {
+            let query = GraphQLQuery {
+                variables: json!({
+                    "owner": self.owner,
+                    "name": self.name,
+                    "label": name,
+                }),
+                query: String::from(include_str!("../data/graphql/queries/get_label.graphql")),
+                operation_name: String::from("GetLabel"),
+            };
+            let root: Root<RepositoryData<LabelData>> =
+                crate::api::v4::request(token, query).await?;
+            match root.data.repository.and_then(|r| r.label) {
+                Some(label) => ids.push(label.id),
+                None => return Err(AppErr::NotFound(format!("label '{}'", name))),
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Resolve the id of an open milestone, matched either by its number or its title
+    pub async fn milestone_id(&self, token: &str, milestone: &str) -> Result<String, AppErr> {
+        let number: Option<u32> = milestone.parse().ok();
+        let query = GraphQLQuery {
+            variables: json!({
+                "owner": self.owner,
+                "name": self.name,
+                "title": milestone,
+                "number": number.unwrap_or_default(),
+                "byNumber": number.is_some(),
+            }),
+            query: String::from(include_str!("../data/graphql/queries/get_milestone.graphql")),
+            operation_name: String::from("GetMilestone"),
+        };
+        let root: Root<RepositoryData<MilestoneData>> =
+            crate::api::v4::request(token, query).await?;
+
+        // Searching by title also matches milestones which only contain the title
+        let candidates: Vec<Milestone> = match root.data.repository {
+            Some(MilestoneData {
+                milestone: Some(milestone),
+                ..
+            }) => vec![milestone],
+            Some(MilestoneData {
+                milestones: Some(milestones),
+                ..
+            }) => milestones
+                .nodes
+                .into_iter()
+                .filter(|m| m.title.eq_ignore_ascii_case(milestone))
+                .collect(),
+            _ => Vec::with_capacity(0),
+        };
+        candidates
+            .into_iter()
+            .find(|m| m.state == "OPEN")
+            .map(|m| m.id)
+            .ok_or_else(|| AppErr::NotFound(format!("milestone '{}'", milestone)))
+    }
+}
+
+pub async fn get_repository(
+    token: &str,
+    owner: &str,
+    name: &str,
+) -> Result<RepositoryMeta, AppErr> {
+    let query = GraphQLQuery {
+        variables: json!({
+            "owner": owner,
+            "name": name,
+        }),
+        query: String::from(include_str!("../data/graphql/queries/get_repository.graphql")),
+        operation_name: String::from("GetRepository"),
+    };
+
+    let root: Root<RepositoryData<RepositoryMeta>> = crate::api::v4::request(token, query).await?;
+    match root.data.repository {
+        Some(repo) => Ok(RepositoryMeta {
+            owner: owner.to_string(),
+            name: name.to_string(),
+            ..repo
+        }),
+        None => Err(AppErr::NotFound(format!("{}/{}", owner, name))),
+    }
+}
+
+/// Resolve the node id of each user
+pub async fn user_ids(token: &str, logins: &[String]) -> Result<Vec<String>, AppErr> {
+    let mut ids: Vec<String> = Vec::with_capacity(logins.len());
+    for login in logins {
+        let login: &str = login.trim_start_matches('@');
+        let query = GraphQLQuery {
+            variables: json!({ "login": login }),
+            query: String::from(include_str!("../data/graphql/queries/get_user_id.graphql")),
+            operation_name: String::from("GetUserId"),
+        };
+        let root: Root<UserData> = crate::api::v4::request(token, query).await?;
+        match root.data.user {
+            Some(user) => ids.push(user.id),
+            None => return Err(AppErr::NotFound(format!("user '{}'", login))),
+        }
+    }
+    Ok(ids)
+}
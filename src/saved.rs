@@ -0,0 +1,223 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{cfg::RunArgs, settings::config_dir, AppErr};
+
+/// A saved search, which is the arguments that were given to `giss save` after the name of the
+/// search. These are the same arguments as for `giss list`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Search {
+    pub args: Vec<String>,
+}
+
+type Searches = BTreeMap<String, Search>;
+
+/// File where saved searches are kept, in the same directory as the configuration file
+pub fn path() -> PathBuf {
+    config_dir().join("saved.toml")
+}
+
+fn read() -> Result<Searches, AppErr> {
+    read_from(&path())
+}
+
+fn read_from(path: &Path) -> Result<Searches, AppErr> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => toml::from_str(&content).map_err(|e| AppErr::InvalidConfig(e.to_string())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Searches::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write(searches: &Searches) -> Result<(), AppErr> {
+    write_to(&path(), searches)
+}
+
+fn write_to(path: &Path, searches: &Searches) -> Result<(), AppErr> {
+    let content: String =
+        toml::to_string(searches).map_err(|e| AppErr::InvalidConfig(e.to_string()))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// Save a search under `name`, replacing any search with the same name. Returns true if a search
+/// was replaced.
+pub fn save(name: &str, args: &[String]) -> Result<bool, AppErr> {
+    save_to(&path(), name, args)
+}
+
+fn save_to(path: &Path, name: &str, args: &[String]) -> Result<bool, AppErr> {
+    if name.is_empty() || name.starts_with('-') || name.contains(char::is_whitespace) {
+        return Err(AppErr::InvalidInput(format!("Invalid name of search '{}'", name)));
+    }
+    crate::cfg::validate_list_args(args)?;
+    let mut searches: Searches = read_from(path)?;
+    let search = Search {
+        args: args.to_vec(),
+    };
+    let replaced: bool = searches.insert(name.to_string(), search).is_some();
+    write_to(path, &searches)?;
+    Ok(replaced)
+}
+
+/// All saved searches, ordered by name
+pub fn list() -> Result<Vec<(String, Search)>, AppErr> {
+    Ok(read()?.into_iter().collect())
+}
+
+pub fn get(name: &str) -> Result<Search, AppErr> {
+    read()?.remove(name).ok_or_else(|| not_found(name))
+}
+
+pub fn delete(name: &str) -> Result<(), AppErr> {
+    let mut searches: Searches = read()?;
+    searches.remove(name).ok_or_else(|| not_found(name))?;
+    write(&searches)
+}
+
+/// Edit the arguments of a saved search in the user's editor, with one argument per line
+pub fn edit(name: &str) -> Result<Search, AppErr> {
+    let search: Search = get(name)?;
+    let initial: String = format!(
+        "# Arguments of saved search '{}', one per line. Lines starting with # are ignored.\n{}\n",
+        name,
+        search.args.join("\n")
+    );
+    let args: Vec<String> = crate::editor::edit(&initial)?
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .map(|line| line.to_string())
+        .collect();
+    save(name, &args)?;
+    Ok(Search { args })
+}
+
+/// Replace `run <name>` in the command line arguments `argv` with `list` and the arguments of the
+/// saved search, followed by any arguments that were given before `run` and any additional
+/// arguments to `run`. Arguments before `run` are moved after `list`, since options for listing
+/// must be given after the subcommand, and global options may be given anywhere.
+pub fn expand(argv: Vec<String>, run: &RunArgs) -> Result<Vec<String>, AppErr> {
+    let search: Search = get(&run.name)?;
+    splice(argv, run, search)
+}
+
+fn splice(argv: Vec<String>, run: &RunArgs, search: Search) -> Result<Vec<String>, AppErr> {
+    let name: &str = &run.name;
+    let index: usize = argv
+        .windows(2)
+        .position(|w| w[0] == "run" && w[1] == name)
+        .ok_or_else(|| not_found(name))?;
+
+    let mut expanded: Vec<String> = argv[..1].to_vec();
+    expanded.push(String::from("list"));
+    expanded.extend(search.args);
+    expanded.extend_from_slice(&argv[1..index]);
+    expanded.extend_from_slice(&run.args);
+    log::debug!("Running saved search '{}' as {:?}", name, expanded);
+    Ok(expanded)
+}
+
+fn not_found(name: &str) -> AppErr {
+    AppErr::NotFound(format!("saved search '{}'", name))
+}
+
+/// Arguments joined as they could be given in a shell, with arguments that contain whitespace
+/// or quotes in single quotes
+pub fn join(args: &[String]) -> String {
+    let quote = |arg: &String| -> String {
+        match arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || "'\"$*".contains(c)) {
+            true => format!("'{}'", arg.replace('\'', "'\\''")),
+            false => arg.clone(),
+        }
+    };
+    args.iter().map(quote).collect::<Vec<String>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn run(name: &str, args: &[&str]) -> RunArgs {
+        RunArgs {
+            name: name.to_string(),
+            args: strings(args),
+        }
+    }
+
+    fn search(args: &[&str]) -> Search {
+        Search {
+            args: strings(args),
+        }
+    }
+
+    #[test]
+    fn saved_searches_are_replaced_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let path: PathBuf = dir.path().join("giss").join("saved.toml");
+        assert!(!save_to(&path, "triage", &strings(&["-i", "mantono/giss"])).unwrap());
+        assert!(!save_to(&path, "mine", &strings(&["-a"])).unwrap());
+        assert!(save_to(&path, "triage", &strings(&["-i", "--no", "assignee"])).unwrap());
+
+        let searches: Searches = read_from(&path).unwrap();
+        let names: Vec<&String> = searches.keys().collect();
+        assert_eq!(vec!["mine", "triage"], names);
+        assert_eq!(strings(&["-i", "--no", "assignee"]), searches["triage"].args);
+    }
+
+    #[test]
+    fn invalid_searches_are_not_saved() {
+        let dir = tempfile::tempdir().unwrap();
+        let path: PathBuf = dir.path().join("saved.toml");
+        for name in ["", "-i", "my search"] {
+            assert!(save_to(&path, name, &strings(&["-i"])).is_err(), "{:?}", name);
+        }
+        assert!(save_to(&path, "triage", &strings(&["--no-such-option"])).is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn missing_file_has_no_searches() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(read_from(&dir.path().join("saved.toml")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn run_is_replaced_by_saved_arguments() {
+        let argv: Vec<String> = strings(&["giss", "run", "triage", "-n", "5"]);
+        let expanded = splice(argv, &run("triage", &["-n", "5"]), search(&["-i", "mantono/giss"]));
+        let expected: Vec<String> = strings(&["giss", "list", "-i", "mantono/giss", "-n", "5"]);
+        assert_eq!(expected, expanded.unwrap());
+    }
+
+    #[test]
+    fn arguments_before_run_are_moved_after_list() {
+        let argv: Vec<String> = strings(&["giss", "--explain", "-u", "x", "run", "triage"]);
+        let expanded = splice(argv, &run("triage", &[]), search(&["-i"]));
+        let expected: Vec<String> = strings(&["giss", "list", "-i", "--explain", "-u", "x"]);
+        assert_eq!(expected, expanded.unwrap());
+    }
+
+    #[test]
+    fn name_of_search_must_follow_run() {
+        let argv: Vec<String> = strings(&["giss", "run", "other"]);
+        assert!(splice(argv, &run("triage", &[]), search(&["-i"])).is_err());
+    }
+
+    #[test]
+    fn arguments_are_quoted_when_joined() {
+        let args: Vec<String> = strings(&["-i", "-S", "crash on start", "", "won't", "$HOME"]);
+        let expected = r#"-i -S 'crash on start' '' 'won'\''t' '$HOME'"#;
+        assert_eq!(expected, join(&args));
+    }
+}
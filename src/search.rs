@@ -1,4 +1,8 @@
-use crate::{list::StateFilter, project::Project};
+use crate::{
+    list::StateFilter,
+    project::Project,
+    qualifier::{quote, Qualifiers},
+};
 use crate::{sort::Sorting, Target};
 use itertools::Itertools;
 use serde::Deserialize;
@@ -45,9 +49,13 @@ pub struct SearchIssues {
     pub project: Option<Project>,
     pub resource_type: Option<Type>,
     pub targets: Vec<Target>,
+    pub qualifiers: Qualifiers,
     pub sort: Sorting,
     pub search: Option<String>,
+    /// Raw search query, which is added as is after all other qualifiers
+    pub query: Option<String>,
     pub limit: u32,
+    pub cursor: Option<String>,
 }
 
 impl SearchQuery for SearchIssues {
@@ -55,11 +63,11 @@ impl SearchQuery for SearchIssues {
         match self.resource_type {
             Some(Type::Issue) => Some(String::from("type:issue")),
             Some(Type::PullRequest) => Some(String::from("type:pr")),
-            Some(Type::ReviewRequest) => {
-                let reviewer = self.review_requested.as_ref().expect("Reviewer was not sent");
-                let query: String = format!("type:pr review-requested:{}", reviewer);
-                Some(query)
-            }
+            // Review requests are only searched for when the user is known
+            Some(Type::ReviewRequest) => match &self.review_requested {
+                Some(reviewer) => Some(format!("type:pr review-requested:{}", reviewer)),
+                None => Some(String::from("type:pr")),
+            },
             None => None,
         }
     }
@@ -73,8 +81,10 @@ impl SearchQuery for SearchIssues {
             self.users(),
             self.labels(),
             self.project(),
+            self.qualifiers(),
             Some(self.sort()),
             self.search(),
+            self.query.clone(),
         ]
         .iter()
         .filter_map(|v| v.clone())
@@ -86,7 +96,8 @@ impl SearchQuery for SearchIssues {
         GraphQLQuery {
             variables: json!({
                 "searchQuery": search_query,
-                "limit": self.limit
+                "limit": self.limit,
+                "cursor": self.cursor
             }),
             query: String::from(include_str!("../data/graphql/queries/search_issues.graphql")),
             operation_name: String::from("SearchIssues"),
@@ -104,14 +115,19 @@ impl SearchIssues {
     }
 
     fn assignee(&self) -> Option<String> {
-        match &self.assignee {
-            Some(name) => Some(format!("assignee:{}", name)),
-            None => None,
+        self.assignee.as_ref().map(|name| format!("assignee:{}", name))
+    }
+
+    fn qualifiers(&self) -> Option<String> {
+        let qualifiers: String = self.qualifiers.to_string();
+        match qualifiers.is_empty() {
+            true => None,
+            false => Some(qualifiers),
         }
     }
 
     fn archived(&self) -> String {
-        String::from("archived:false")
+        format!("archived:{}", self.archived)
     }
 
     fn users(&self) -> Option<String> {
@@ -127,7 +143,7 @@ impl SearchIssues {
         if self.labels.is_empty() {
             None
         } else {
-            Some(self.labels.iter().map(|l| format!("label:{}", l)).join(" "))
+            Some(self.labels.iter().map(|l| format!("label:{}", quote(l))).join(" "))
         }
     }
 
@@ -0,0 +1,198 @@
+use std::{collections::HashMap, fmt::Display, path::PathBuf, str::FromStr};
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    api::Host,
+    cfg::Flag,
+    output::OutputFormat,
+    sort::{Order, Property},
+    user::Username,
+    AppErr,
+};
+
+/// Settings read from the configuration file, located at `~/.config/giss/config.toml`.
+///
+/// Any setting at the top level of the file is used as a default, while settings in a named
+/// profile, such as `[profile.oncall]`, are only used when that profile is selected. Settings in a
+/// selected profile takes precedence over the defaults.
+#[derive(Debug, Default)]
+pub struct Settings {
+    defaults: Profile,
+    profile: HashMap<String, Profile>,
+}
+
+/// Settings are not derived with `#[serde(flatten)]` for the defaults, since unknown fields can
+/// then not be rejected
+impl<'de> Deserialize<'de> for Settings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut table: toml::value::Table = toml::value::Table::deserialize(deserializer)?;
+        let profile: HashMap<String, Profile> = match table.remove("profile") {
+            Some(profile) => profile.try_into().map_err(D::Error::custom)?,
+            None => HashMap::new(),
+        };
+        let defaults: Profile = toml::Value::Table(table).try_into().map_err(D::Error::custom)?;
+        Ok(Settings { defaults, profile })
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    #[serde(default, deserialize_with = "parse", serialize_with = "display")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<Host>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oauth_client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub targets: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_by: Option<Property>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub colors: Option<Flag>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub links: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assigned: Option<bool>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<Username>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<OutputFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify_command: Option<String>,
+}
+
+impl Settings {
+    /// Read settings from the configuration file. If there is no such file, default (empty)
+    /// settings are returned.
+    pub fn read() -> Result<Settings, AppErr> {
+        let path: PathBuf = config_file();
+        if !path.exists() {
+            log::debug!("No configuration file found at {:?}", path);
+            return Ok(Settings::default());
+        }
+        log::debug!("Using configuration file {:?}", path);
+        let content: String = std::fs::read_to_string(&path)?;
+        toml::from_str(&content).map_err(|e| AppErr::InvalidConfig(e.to_string()))
+    }
+
+    /// Resolve the settings to use, with settings from the profile `name` (if any) overriding
+    /// the defaults
+    pub fn profile(self, name: Option<&str>) -> Result<Profile, AppErr> {
+        let Settings {
+            defaults,
+            mut profile,
+        } = self;
+
+        match name {
+            Some(name) => match profile.remove(name) {
+                Some(profile) => Ok(defaults.merge(profile)),
+                None => Err(AppErr::UnknownProfile(name.to_string())),
+            },
+            None => Ok(defaults),
+        }
+    }
+}
+
+impl Profile {
+    fn merge(self, other: Profile) -> Profile {
+        Profile {
+            host: other.host.or(self.host),
+            oauth_client_id: other.oauth_client_id.or(self.oauth_client_id),
+            targets: other.targets.or(self.targets),
+            limit: other.limit.or(self.limit),
+            sort_by: other.sort_by.or(self.sort_by),
+            order: other.order.or(self.order),
+            colors: other.colors.or(self.colors),
+            links: other.links.or(self.links),
+            labels: other.labels.or(self.labels),
+            assigned: other.assigned.or(self.assigned),
+            user: other.user.or(self.user),
+            output: other.output.or(self.output),
+            notify_command: other.notify_command.or(self.notify_command),
+        }
+    }
+}
+
+fn parse<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(value) => value.parse::<T>().map(Some).map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+fn display<S, T>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Display,
+{
+    match value {
+        Some(value) => serializer.serialize_str(&value.to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Directory of the configuration file, saved searches and stored tokens, which can be moved with
+/// the environment variable `GISS_CONFIG_DIR`
+pub fn config_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("GISS_CONFIG_DIR") {
+        return PathBuf::from(dir);
+    }
+    let mut path: PathBuf = dirs_next::home_dir().expect("Cannot find home dir");
+    path.push([".config", "giss"].join("/"));
+    path
+}
+
+pub fn config_file() -> PathBuf {
+    config_dir().join("config.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_overrides_defaults() {
+        let settings: Settings = toml::from_str(
+            "limit = 20\nlinks = true\n\n[profile.oncall]\nlinks = false\nassigned = true\n",
+        )
+        .unwrap();
+        let profile: Profile = settings.profile(Some("oncall")).unwrap();
+        assert_eq!(Some(20), profile.limit);
+        assert_eq!(Some(false), profile.links);
+        assert_eq!(Some(true), profile.assigned);
+    }
+
+    #[test]
+    fn unknown_profile_is_an_error() {
+        let settings: Settings = toml::from_str("limit = 20\n").unwrap();
+        assert!(matches!(settings.profile(Some("x")), Err(AppErr::UnknownProfile(_))));
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(toml::from_str::<Settings>("limt = 20\n").is_err());
+        assert!(toml::from_str::<Settings>("[profile.oncall]\nlnks = true\n").is_err());
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        assert!(toml::from_str::<Settings>("sort_by = \"stars\"\n").is_err());
+    }
+}
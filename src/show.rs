@@ -0,0 +1,198 @@
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    github_resources::ghrs,
+    issue::{AssigneeNode, LabelNode, Repository},
+    search::{GraphQLQuery, Type},
+    target::IssueRef,
+    AppErr,
+};
+
+#[derive(Debug, Deserialize)]
+struct Root {
+    data: Data,
+}
+
+#[derive(Debug, Deserialize)]
+struct Data {
+    repository: Option<RepositoryNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepositoryNode {
+    #[serde(alias = "issueOrPullRequest")]
+    issue_or_pull_request: Option<IssueDetails>,
+}
+
+/// An issue or pull request, with all details needed to present it on its own
+#[derive(Debug, Deserialize)]
+pub struct IssueDetails {
+    #[serde(alias = "__typename")]
+    pub kind: Type,
+    pub number: u32,
+    pub title: String,
+    pub url: String,
+    #[serde(alias = "issueState")]
+    #[serde(alias = "pullRequestState")]
+    pub state: ghrs::State,
+    #[serde(alias = "stateReason")]
+    pub state_reason: Option<ghrs::StateReason>,
+    pub author: Option<Actor>,
+    #[serde(alias = "createdAt")]
+    pub created_at: String,
+    #[serde(alias = "updatedAt")]
+    pub updated_at: String,
+    pub body: String,
+    #[serde(alias = "isDraft")]
+    pub is_draft: Option<bool>,
+    #[serde(alias = "reviewDecision")]
+    pub review_decision: Option<String>,
+    pub assignees: AssigneeNode,
+    pub labels: LabelNode,
+    pub repository: Repository,
+    pub comments: CommentNode,
+    pub reviews: Option<ReviewNode>,
+    pub commits: Option<CommitNode>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Actor {
+    pub login: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommentNode {
+    #[serde(alias = "totalCount")]
+    pub total_count: u32,
+    pub nodes: Vec<Comment>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Comment {
+    pub author: Option<Actor>,
+    #[serde(alias = "createdAt")]
+    pub created_at: String,
+    pub body: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewNode {
+    pub nodes: Vec<Review>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Review {
+    pub author: Option<Actor>,
+    pub state: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitNode {
+    pub nodes: Vec<CommitWrapper>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitWrapper {
+    pub commit: Commit,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Commit {
+    #[serde(alias = "statusCheckRollup")]
+    pub status_check_rollup: Option<StatusCheckRollup>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatusCheckRollup {
+    pub state: String,
+    pub contexts: CheckContextNode,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckContextNode {
+    pub nodes: Vec<CheckContext>,
+}
+
+/// Either a `CheckRun` (from GitHub Actions or any other GitHub App) or a `StatusContext` (from
+/// the older commit status API)
+#[derive(Debug, Deserialize)]
+pub struct CheckContext {
+    pub name: Option<String>,
+    pub status: Option<String>,
+    pub conclusion: Option<String>,
+    pub context: Option<String>,
+    pub state: Option<String>,
+}
+
+impl CheckContext {
+    pub fn name(&self) -> &str {
+        self.name.as_deref().or(self.context.as_deref()).unwrap_or("unknown")
+    }
+
+    /// Result of the check, which will be the status of the check if it has not completed yet
+    pub fn result(&self) -> &str {
+        self.conclusion
+            .as_deref()
+            .or(self.state.as_deref())
+            .or(self.status.as_deref())
+            .unwrap_or("UNKNOWN")
+    }
+}
+
+impl IssueDetails {
+    pub fn author(&self) -> &str {
+        self.author.as_ref().map(|a| a.login.as_str()).unwrap_or("ghost")
+    }
+
+    /// Latest review, that is not only a comment, from each reviewer
+    pub fn latest_reviews(&self) -> Vec<&Review> {
+        let reviews: &[Review] = match &self.reviews {
+            Some(reviews) => &reviews.nodes,
+            None => return Vec::with_capacity(0),
+        };
+        let mut latest: Vec<&Review> = Vec::with_capacity(reviews.len());
+        for review in reviews.iter().filter(|r| r.state != "COMMENTED") {
+            latest.retain(|r| r.author() != review.author());
+            latest.push(review);
+        }
+        latest
+    }
+
+    pub fn checks(&self) -> Option<&StatusCheckRollup> {
+        self.commits
+            .as_ref()
+            .and_then(|c| c.nodes.last())
+            .and_then(|c| c.commit.status_check_rollup.as_ref())
+    }
+}
+
+impl Review {
+    pub fn author(&self) -> &str {
+        self.author.as_ref().map(|a| a.login.as_str()).unwrap_or("ghost")
+    }
+}
+
+impl Comment {
+    pub fn author(&self) -> &str {
+        self.author.as_ref().map(|a| a.login.as_str()).unwrap_or("ghost")
+    }
+}
+
+pub async fn get_issue(token: &str, issue: &IssueRef) -> Result<IssueDetails, AppErr> {
+    let query = GraphQLQuery {
+        variables: json!({
+            "owner": issue.owner,
+            "name": issue.repo,
+            "number": issue.number,
+        }),
+        query: String::from(include_str!("../data/graphql/queries/get_issue.graphql")),
+        operation_name: String::from("GetIssue"),
+    };
+
+    let root: Root = crate::api::v4::request(token, query).await?;
+    root.data
+        .repository
+        .and_then(|r| r.issue_or_pull_request)
+        .ok_or_else(|| AppErr::NotFound(issue.to_string()))
+}
@@ -20,9 +20,10 @@ impl Sorting {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 pub enum Property {
     Created,
+    #[default]
     Updated,
     Comments,
     Reactions,
@@ -48,12 +49,6 @@ impl Order {
     }
 }
 
-impl Default for Property {
-    fn default() -> Self {
-        Property::Updated
-    }
-}
-
 impl FromStr for Property {
     type Err = &'static str;
 
@@ -73,25 +68,20 @@ impl Display for Property {
         let s: &str = match self {
             Property::Created => "created",
             Property::Updated => "updated",
-            Property::Comments => "comments;",
+            Property::Comments => "comments",
             Property::Reactions => "reactions",
         };
         write!(f, "{}", s)
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 pub enum Order {
+    #[default]
     Descending,
     Ascending,
 }
 
-impl Default for Order {
-    fn default() -> Self {
-        Order::Descending
-    }
-}
-
 impl FromStr for Order {
     type Err = &'static str;
 
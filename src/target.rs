@@ -1,3 +1,8 @@
+use crate::{
+    git::{self, Remote},
+    issue::Issue,
+    AppErr,
+};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::{fmt, str::FromStr};
@@ -9,22 +14,30 @@ pub enum Target {
 }
 
 lazy_static! {
-    static ref TARGET: Regex = Regex::new(r"[\w\-\.]+").unwrap();
+    static ref TARGET: Regex = Regex::new(r"^@?([\w\-\.]+)(?:/([\w\-\.]+))?$").unwrap();
+}
+
+impl Target {
+    /// Resolve the repository in the current directory as a target
+    pub fn current() -> Result<Target, AppErr> {
+        let remote: Remote = git::current_remote()?;
+        Ok(Target::Repository(remote.owner, remote.repo))
+    }
 }
 
 impl FromStr for Target {
     type Err = String;
 
+    /// Parse a target, which is either an organization or user such as `kotlin` (optionally
+    /// given as `@kotlin`), or a repository such as `mantono/giss`
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<String> = TARGET
-            .find_iter(s)
-            .into_iter()
-            .map(|x| x.as_str().to_string())
-            .collect();
-        match parts.len() {
-            1 => Ok(Target::Organization(parts[0].clone())),
-            2 => Ok(Target::Repository(parts[0].clone(), parts[1].clone())),
-            _ => Err(format!("Could not resolve a valid target from '{}'", s)),
+        let captures = TARGET
+            .captures(s.trim())
+            .ok_or_else(|| format!("Could not resolve a valid target from '{}'", s))?;
+        let owner: String = captures[1].to_string();
+        match captures.get(2) {
+            Some(repo) => Ok(Target::Repository(owner, repo.as_str().to_string())),
+            None => Ok(Target::Organization(owner)),
         }
     }
 }
@@ -46,3 +59,94 @@ impl Clone for Target {
         }
     }
 }
+
+/// Reference to a single issue or pull request, such as `owner/repo#123`. When only the number
+/// is given (`#123` or `123`), the repository in the current directory will be used.
+#[derive(Debug, Clone)]
+pub struct IssueRef {
+    pub owner: String,
+    pub repo: String,
+    pub number: u32,
+}
+
+lazy_static! {
+    static ref ISSUE_REF: Regex = Regex::new(r"^(?:([\w\-\.]+)/([\w\-\.]+))?#?(\d+)$").unwrap();
+}
+
+impl FromStr for IssueRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid reference '{}', expected format owner/repo#number", s);
+        let captures = ISSUE_REF.captures(s).ok_or_else(invalid)?;
+        let number: u32 = captures[3].parse::<u32>().map_err(|_| invalid())?;
+
+        match (captures.get(1), captures.get(2)) {
+            (Some(owner), Some(repo)) => Ok(IssueRef {
+                owner: owner.as_str().to_string(),
+                repo: repo.as_str().to_string(),
+                number,
+            }),
+            _ => match Target::current() {
+                Ok(Target::Repository(owner, repo)) => Ok(IssueRef {
+                    owner,
+                    repo,
+                    number,
+                }),
+                _ => Err(format!(
+                    "No repository given for '{}' and none found in current directory",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl From<&Issue> for IssueRef {
+    fn from(issue: &Issue) -> Self {
+        let (owner, repo) = issue.repository.name_with_owner.split_once('/').unwrap_or_default();
+        IssueRef {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            number: issue.number,
+        }
+    }
+}
+
+impl fmt::Display for IssueRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}#{}", self.owner, self.repo, self.number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(s: &str) -> Result<String, String> {
+        Target::from_str(s).map(|target| target.to_string())
+    }
+
+    #[test]
+    fn organizations() {
+        assert_eq!(Ok(String::from("org:kotlin")), target("kotlin"));
+        assert_eq!(Ok(String::from("org:mantono")), target("@mantono"));
+        assert_eq!(Ok(String::from("org:my-org")), target(" my-org "));
+    }
+
+    #[test]
+    fn repositories() {
+        assert_eq!(Ok(String::from("repo:mantono/giss")), target("mantono/giss"));
+        assert_eq!(Ok(String::from("repo:my-org/my.repo")), target("my-org/my.repo"));
+        assert_eq!(Ok(String::from("repo:mantono/giss")), target("@mantono/giss"));
+    }
+
+    #[test]
+    fn invalid_targets() {
+        assert!(target("").is_err());
+        assert!(target("mantono/").is_err());
+        assert!(target("a/b/c").is_err());
+        assert!(target("org:kotlin").is_err());
+        assert!(target("https://github.com/mantono/giss").is_err());
+    }
+}
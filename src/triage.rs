@@ -0,0 +1,190 @@
+use std::io::{IsTerminal, Read};
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    search::{GraphQLQuery, Type},
+    target::IssueRef,
+    AppErr,
+};
+
+/// An issue or pull request that is to be modified, identified by its node id
+#[derive(Debug)]
+pub struct Subject {
+    pub id: String,
+    pub kind: Type,
+}
+
+#[derive(Debug, Deserialize)]
+struct Root<T> {
+    data: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct Data {
+    repository: Option<RepositoryNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepositoryNode {
+    #[serde(alias = "issueOrPullRequest")]
+    issue_or_pull_request: Option<SubjectNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubjectNode {
+    #[serde(alias = "__typename")]
+    kind: Type,
+    id: String,
+}
+
+/// Look up the node id of an issue or pull request
+pub async fn resolve(token: &str, issue: &IssueRef) -> Result<Subject, AppErr> {
+    let query = GraphQLQuery {
+        variables: json!({
+            "owner": issue.owner,
+            "name": issue.repo,
+            "number": issue.number,
+        }),
+        query: String::from(include_str!("../data/graphql/queries/get_issue_id.graphql")),
+        operation_name: String::from("GetIssueId"),
+    };
+
+    let root: Root<Data> = crate::api::v4::request(token, query).await?;
+    root.data
+        .repository
+        .and_then(|r| r.issue_or_pull_request)
+        .map(|n| Subject {
+            id: n.id,
+            kind: n.kind,
+        })
+        .ok_or_else(|| AppErr::NotFound(issue.to_string()))
+}
+
+pub async fn comment(token: &str, subject: &Subject, body: &str) -> Result<(), AppErr> {
+    let query = GraphQLQuery {
+        variables: json!({ "subjectId": subject.id, "body": body }),
+        query: String::from(include_str!("../data/graphql/mutations/add_comment.graphql")),
+        operation_name: String::from("AddComment"),
+    };
+    mutate(token, query).await
+}
+
+pub async fn close(token: &str, subject: &Subject) -> Result<(), AppErr> {
+    let query = match subject.kind {
+        Type::Issue => GraphQLQuery {
+            variables: json!({ "id": subject.id }),
+            query: String::from(include_str!("../data/graphql/mutations/close_issue.graphql")),
+            operation_name: String::from("CloseIssue"),
+        },
+        Type::PullRequest | Type::ReviewRequest => GraphQLQuery {
+            variables: json!({ "id": subject.id }),
+            query: String::from(include_str!(
+                "../data/graphql/mutations/close_pull_request.graphql"
+            )),
+            operation_name: String::from("ClosePullRequest"),
+        },
+    };
+    mutate(token, query).await
+}
+
+pub async fn reopen(token: &str, subject: &Subject) -> Result<(), AppErr> {
+    let query = match subject.kind {
+        Type::Issue => GraphQLQuery {
+            variables: json!({ "id": subject.id }),
+            query: String::from(include_str!("../data/graphql/mutations/reopen_issue.graphql")),
+            operation_name: String::from("ReopenIssue"),
+        },
+        Type::PullRequest | Type::ReviewRequest => GraphQLQuery {
+            variables: json!({ "id": subject.id }),
+            query: String::from(include_str!(
+                "../data/graphql/mutations/reopen_pull_request.graphql"
+            )),
+            operation_name: String::from("ReopenPullRequest"),
+        },
+    };
+    mutate(token, query).await
+}
+
+pub async fn assign(
+    token: &str,
+    subject: &Subject,
+    user_ids: &[String],
+    remove: bool,
+) -> Result<(), AppErr> {
+    let query = match remove {
+        false => GraphQLQuery {
+            variables: json!({ "id": subject.id, "assigneeIds": user_ids }),
+            query: String::from(include_str!("../data/graphql/mutations/add_assignees.graphql")),
+            operation_name: String::from("AddAssignees"),
+        },
+        true => GraphQLQuery {
+            variables: json!({ "id": subject.id, "assigneeIds": user_ids }),
+            query: String::from(include_str!(
+                "../data/graphql/mutations/remove_assignees.graphql"
+            )),
+            operation_name: String::from("RemoveAssignees"),
+        },
+    };
+    mutate(token, query).await
+}
+
+pub async fn label(
+    token: &str,
+    subject: &Subject,
+    label_ids: &[String],
+    remove: bool,
+) -> Result<(), AppErr> {
+    let query = match remove {
+        false => GraphQLQuery {
+            variables: json!({ "id": subject.id, "labelIds": label_ids }),
+            query: String::from(include_str!("../data/graphql/mutations/add_labels.graphql")),
+            operation_name: String::from("AddLabels"),
+        },
+        true => GraphQLQuery {
+            variables: json!({ "id": subject.id, "labelIds": label_ids }),
+            query: String::from(include_str!("../data/graphql/mutations/remove_labels.graphql")),
+            operation_name: String::from("RemoveLabels"),
+        },
+    };
+    mutate(token, query).await
+}
+
+/// Execute a mutation. A mutation that could not be performed is reported by GitHub with a
+/// `null` result for the mutation, together with one or more errors.
+async fn mutate(token: &str, query: GraphQLQuery) -> Result<(), AppErr> {
+    let operation: String = query.operation_name.clone();
+    let root: Root<serde_json::Map<String, serde_json::Value>> =
+        crate::api::v4::request(token, query).await?;
+    match root.data.values().all(|v| !v.is_null()) {
+        true => Ok(()),
+        false => {
+            log::error!("Mutation {} was not performed", operation);
+            Err(AppErr::ApiError)
+        }
+    }
+}
+
+/// Resolve the body of a comment, which is taken from `message` if given, or else from stdin
+/// if it is not a terminal, or else written by the user in an editor. A message of "-" will
+/// always read from stdin.
+pub fn message(message: &Option<String>) -> Result<String, AppErr> {
+    let body: String = match message.as_deref() {
+        Some("-") => read_stdin()?,
+        Some(message) => message.to_string(),
+        None if !std::io::stdin().is_terminal() => read_stdin()?,
+        None => crate::editor::edit("")?,
+    };
+
+    match body.trim() {
+        "" => Err(AppErr::InvalidInput(String::from("Comment must not be empty"))),
+        body => Ok(body.to_string()),
+    }
+}
+
+fn read_stdin() -> Result<String, AppErr> {
+    let mut body = String::new();
+    std::io::stdin().read_to_string(&mut body)?;
+    Ok(body)
+}
@@ -1,5 +1,8 @@
-use std::{io::Write, sync::mpsc::RecvTimeoutError};
-use std::{sync::mpsc::Receiver, time::Duration};
+use crossterm::{
+    cursor::MoveTo,
+    terminal::{Clear, ClearType},
+};
+use std::sync::mpsc::Receiver;
 
 use itertools::Itertools;
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
@@ -7,10 +10,16 @@ use user::Username;
 
 use crate::{
     cfg::Config,
+    github_resources::ghrs,
+    inbox::Thread,
     issue::{Issue, Label, UserFields},
-    search::Type,
+    output::{IssueRecord, OutputFormat},
+    search::{GraphQLQuery, Type},
+    show::{CheckContext, IssueDetails},
     sort::Sorting,
-    user, AppErr,
+    user,
+    watch::Change,
+    AppErr,
 };
 
 pub struct DisplayConfig {
@@ -19,6 +28,7 @@ pub struct DisplayConfig {
     user: Option<Username>,
     limit: u32,
     links: bool,
+    output: OutputFormat,
 }
 
 impl From<&Config> for DisplayConfig {
@@ -29,36 +39,78 @@ impl From<&Config> for DisplayConfig {
             user: cfg.username(),
             sorting: cfg.sorting(),
             links: cfg.show_links(),
+            output: cfg.output(),
         }
     }
 }
 
-pub fn display(channel: Receiver<Issue>, cfg: DisplayConfig) -> Result<(), AppErr> {
-    let mut limit: u32 = cfg.limit * 3;
-    let mut queue: Vec<Issue> = Vec::with_capacity(limit as usize);
-    while limit > 0 {
-        match channel.recv_timeout(Duration::from_secs(20)) {
-            Ok(issue) => {
-                queue.push(issue);
-                limit -= 1;
-            }
-            Err(e) => match e {
-                RecvTimeoutError::Timeout => return Err(AppErr::Timeout),
-                RecvTimeoutError::Disconnected => limit = 0,
-            },
-        };
+pub fn display(issues: Vec<Issue>, cfg: DisplayConfig) -> Result<(), AppErr> {
+    match cfg.output.records() {
+        None => {
+            let mut stdout = StandardStream::stdout(cfg.colors);
+            issues
+                .iter()
+                .for_each(|i| print_issue(&mut stdout, i, true, &cfg));
+        }
+        Some(format) => {
+            let records: Vec<IssueRecord> = issues
+                .iter()
+                .map(|i| IssueRecord::new(i, kind(i, &cfg)))
+                .collect();
+            crate::output::write_records(&mut std::io::stdout().lock(), &records, format)?;
+        }
+    };
+
+    Ok(())
+}
+
+/// Clear the terminal and print the issues, each marked with how it has changed since it was last
+/// printed, followed by a status line
+pub fn redraw(
+    issues: &[Issue],
+    changes: &[Change],
+    status: &str,
+    cfg: &DisplayConfig,
+) -> Result<(), AppErr> {
+    let mut stdout = StandardStream::stdout(cfg.colors);
+    crossterm::execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+    for (issue, change) in issues.iter().zip(changes) {
+        match change {
+            Change::New => write(&mut stdout, "+ ", Some(Color::Green)),
+            Change::Updated => write(&mut stdout, "* ", Some(Color::Yellow)),
+            Change::Unchanged => write(&mut stdout, "  ", None),
+        }
+        print_issue(&mut stdout, issue, true, cfg);
     }
+    write(&mut stdout, &format!("\n{}\n", status), Some(Color::White));
+    stdout.reset()?;
+    Ok(())
+}
+
+/// Receive all issues from the channel, and return them sorted, deduplicated and limited
+/// according to the configuration
+pub fn collect(channel: Receiver<Issue>, cfg: &DisplayConfig) -> Result<Vec<Issue>, AppErr> {
+    let mut queue: Vec<Issue> = Vec::with_capacity(cfg.limit as usize * 3);
+    // Keep consuming until all senders are done, since issues may be streamed over several pages.
+    // There is no deadline here, since each request is already bounded by the configured timeout
+    // and retries by the longest backoff, after which the sender is dropped.
+    queue.extend(channel.iter());
     queue.sort_unstable_by(|i0, i1| cfg.sorting.sort(i0, i1));
-    queue
+    let issues: Vec<Issue> = queue
         .into_iter()
         .unique_by(|i| i.id)
         .take(cfg.limit as usize)
-        .for_each(|i| print_issue(i, true, &cfg));
-    Ok(())
+        .collect();
+
+    Ok(issues)
 }
 
-fn print_issue(issue: Issue, print_repo: bool, cfg: &DisplayConfig) {
-    let use_colors: ColorChoice = cfg.colors;
+pub fn print_issue<W: WriteColor>(
+    stream: &mut W,
+    issue: &Issue,
+    print_repo: bool,
+    cfg: &DisplayConfig,
+) {
     let title: String = truncate(issue.title.clone(), 50);
     let assignees: String = issue
         .assignees
@@ -84,9 +136,7 @@ fn print_issue(issue: Issue, print_repo: bool, cfg: &DisplayConfig) {
         .collect::<Vec<String>>()
         .join(", ");
 
-    let mut stdout = StandardStream::stdout(use_colors);
-
-    print_type(&mut stdout, &issue, cfg);
+    print_type(stream, kind(issue, cfg), issue.state, issue.state_reason);
 
     let target: String = if print_repo {
         format!("#{} {}", issue.number, repo)
@@ -94,30 +144,114 @@ fn print_issue(issue: Issue, print_repo: bool, cfg: &DisplayConfig) {
         format!("#{}", issue.number)
     };
 
-    write(&mut stdout, target.as_str(), None);
-    delimiter(&mut stdout);
-    write(&mut stdout, &title, None);
+    write(stream, target.as_str(), None);
+    delimiter(stream);
+    write(stream, &title, None);
+
+    if let Type::PullRequest = issue.kind {
+        print_pull_request_status(stream, issue);
+    }
 
     if !assignees.is_empty() {
-        delimiter(&mut stdout);
-        write(&mut stdout, &assignees, Some(Color::Cyan));
+        delimiter(stream);
+        write(stream, &assignees, Some(Color::Cyan));
     }
 
     if !labels.is_empty() {
-        delimiter(&mut stdout);
-        write(&mut stdout, &labels, Some(Color::Magenta));
+        delimiter(stream);
+        write(stream, &labels, Some(Color::Magenta));
     }
 
     if cfg.links {
+        delimiter(stream);
+        write(stream, &issue.link(), Some(Color::Blue));
+    }
+
+    write(stream, "\n", None);
+}
+
+/// Print compact indicators of whether a pull request is ready to be merged: if it is a draft,
+/// the result of its checks, the review decision, any conflicts and the size of the change
+fn print_pull_request_status<W: WriteColor>(stream: &mut W, issue: &Issue) {
+    let mut status: Vec<(String, Color)> = Vec::with_capacity(6);
+    if issue.is_draft == Some(true) {
+        status.push((String::from("draft"), Color::Yellow));
+    }
+    if let Some(checks) = issue.checks() {
+        status.push((check_symbol(checks).to_string(), result_color(checks)));
+    }
+    match issue.review_decision.as_deref() {
+        Some(decision @ "APPROVED") | Some(decision @ "CHANGES_REQUESTED") => {
+            status.push((humanize(decision), result_color(decision)))
+        }
+        _ => {}
+    }
+    if issue.has_conflicts() {
+        status.push((String::from("conflicts"), Color::Red));
+    }
+    if let (Some(additions), Some(deletions)) = (issue.additions, issue.deletions) {
+        status.push((format!("+{}", additions), Color::Green));
+        status.push((format!("-{}", deletions), Color::Red));
+    }
+    if status.is_empty() {
+        return;
+    }
+
+    delimiter(stream);
+    for (i, (text, color)) in status.iter().enumerate() {
+        if i > 0 {
+            write(stream, " ", None);
+        }
+        write(stream, text, Some(*color));
+    }
+}
+
+/// Print notification threads, with the id of each thread first so it can be given to `giss inbox
+/// read` or `giss inbox unsubscribe`. Threads that have been read are printed without colors.
+pub fn display_threads(threads: &[Thread], colors: ColorChoice) {
+    let mut stdout = StandardStream::stdout(colors);
+    for thread in threads {
+        let color = |color: Color| -> Option<Color> {
+            match thread.unread {
+                true => Some(color),
+                false => None,
+            }
+        };
+        let subject: String = match thread.number() {
+            Some(number) => format!("{}#{}", thread.repository.full_name, number),
+            None => thread.repository.full_name.clone(),
+        };
+        write(&mut stdout, &thread.id, color(Color::Yellow));
+        delimiter(&mut stdout);
+        write(&mut stdout, &thread.reason.to_string(), color(Color::Cyan));
+        delimiter(&mut stdout);
+        write(&mut stdout, &subject, None);
+        delimiter(&mut stdout);
+        write(&mut stdout, &thread.subject.kind, color(Color::Magenta));
         delimiter(&mut stdout);
-        write(&mut stdout, &issue.link(), Some(Color::Blue));
+        write(&mut stdout, &truncate(thread.subject.title.clone(), 50), None);
+        write(&mut stdout, "\n", None);
     }
+}
 
-    write(&mut stdout, "\n", None);
+/// Print the search query and variables of GraphQL queries, as given by `list::queries`
+pub fn print_queries(queries: &[(Type, GraphQLQuery)], colors: ColorChoice) -> Result<(), AppErr> {
+    let mut stdout = StandardStream::stdout(colors);
+    for (kind, query) in queries {
+        let search: &str = query.variables["searchQuery"].as_str().unwrap_or_default();
+        print_type(&mut stdout, *kind, ghrs::State::Open, None);
+        write(&mut stdout, search, None);
+        write(&mut stdout, "\n", None);
+        let variables: String = serde_json::to_string_pretty(&query.variables)?;
+        write(&mut stdout, &format!("{}: {}\n", query.operation_name, variables), None);
+    }
+    Ok(())
 }
 
-fn print_type(stream: &mut StandardStream, issue: &Issue, cfg: &DisplayConfig) {
-    let kind: Type = match issue.kind {
+/// Resolve the type of an issue, taking into account whether a pull request is a review request
+/// for the current user
+fn kind(issue: &Issue, cfg: &DisplayConfig) -> Type {
+    match issue.kind {
         Type::Issue => Type::Issue,
         _ => match &cfg.user {
             Some(user) => match issue.has_review_request(&user.0) {
@@ -126,33 +260,220 @@ fn print_type(stream: &mut StandardStream, issue: &Issue, cfg: &DisplayConfig) {
             },
             None => Type::PullRequest,
         },
-    };
+    }
+}
 
+/// Print the type of an issue or pull request, in the color of its state if it is not open
+fn print_type<W: WriteColor>(
+    stream: &mut W,
+    kind: Type,
+    state: ghrs::State,
+    reason: Option<ghrs::StateReason>,
+) {
+    let color = |open: Color| -> Option<Color> { state_color(state, reason).or(Some(open)) };
     match kind {
-        crate::search::Type::Issue => write(stream, "I ", Some(Color::Blue)),
-        crate::search::Type::PullRequest => write(stream, "P ", Some(Color::Magenta)),
+        crate::search::Type::Issue => write(stream, "I ", color(Color::Blue)),
+        crate::search::Type::PullRequest => write(stream, "P ", color(Color::Magenta)),
         crate::search::Type::ReviewRequest => {
-            write(stream, "P", Some(Color::Magenta));
-            write(stream, "R", Some(Color::Yellow));
+            write(stream, "P", color(Color::Magenta));
+            write(stream, "R", color(Color::Yellow));
         }
     };
     write(stream, "| ", Some(Color::Green));
 }
 
-fn delimiter(stream: &mut StandardStream) {
+/// Color of an issue or pull request that is not open, which is different for pull requests
+/// that were merged and issues that were closed as completed or as not planned
+fn state_color(state: ghrs::State, reason: Option<ghrs::StateReason>) -> Option<Color> {
+    match (state, reason) {
+        (ghrs::State::Open, _) => None,
+        (ghrs::State::Merged, _) => Some(Color::Cyan),
+        (ghrs::State::Closed, Some(ghrs::StateReason::NotPlanned))
+        | (ghrs::State::Closed, Some(ghrs::StateReason::Duplicate)) => Some(Color::White),
+        (ghrs::State::Closed, _) => Some(Color::Red),
+    }
+}
+
+/// State of an issue or pull request, with the reason it was closed, if any
+fn describe_state(state: ghrs::State, reason: Option<ghrs::StateReason>) -> String {
+    match (state, reason) {
+        (ghrs::State::Closed, Some(reason)) if reason.is_closed() => {
+            format!("{} ({})", state, reason)
+        }
+        _ => state.to_string(),
+    }
+}
+
+/// Print a single issue or pull request in detail, with body, comments and, for pull requests,
+/// reviews and checks
+pub fn print_details(issue: &IssueDetails, cfg: &DisplayConfig) {
+    write_details(&mut StandardStream::stdout(cfg.colors), issue);
+}
+
+pub fn write_details<W: WriteColor>(stream: &mut W, issue: &IssueDetails) {
+    print_type(stream, issue.kind, issue.state, issue.state_reason);
+    write(
+        stream,
+        &format!("#{} {}", issue.number, issue.repository.name_with_owner),
+        None,
+    );
+    delimiter(stream);
+    write(stream, &issue.title, None);
+    write(stream, "\n\n", None);
+
+    let state: String = match issue.is_draft {
+        Some(true) => format!("{} (draft)", issue.state),
+        _ => describe_state(issue.state, issue.state_reason),
+    };
+    let color: Color = match (issue.is_draft, state_color(issue.state, issue.state_reason)) {
+        (_, Some(color)) => color,
+        (Some(true), None) => Color::Yellow,
+        (_, None) => Color::Green,
+    };
+    field(stream, "State", &state, Some(color));
+    field(
+        stream,
+        "Author",
+        &format!("@{}", issue.author()),
+        Some(Color::Cyan),
+    );
+    field(stream, "Created", &issue.created_at, None);
+    field(stream, "Updated", &issue.updated_at, None);
+
+    let assignees: String = issue
+        .assignees
+        .nodes
+        .iter()
+        .map(|a: &UserFields| format!("@{}", a.login))
+        .join(", ");
+    if !assignees.is_empty() {
+        field(stream, "Assignees", &assignees, Some(Color::Cyan));
+    }
+
+    let labels: String = issue
+        .labels
+        .nodes
+        .iter()
+        .map(|l: &Label| format!("#{}", l.name))
+        .join(", ");
+    if !labels.is_empty() {
+        field(stream, "Labels", &labels, Some(Color::Magenta));
+    }
+
+    if let Some(decision) = &issue.review_decision {
+        field(
+            stream,
+            "Review",
+            &humanize(decision),
+            Some(result_color(decision)),
+        );
+    }
+    for review in issue.latest_reviews() {
+        write(stream, "  ", None);
+        write(stream, &format!("@{}", review.author()), Some(Color::Cyan));
+        write(stream, " ", None);
+        write(
+            stream,
+            &humanize(&review.state),
+            Some(result_color(&review.state)),
+        );
+        write(stream, "\n", None);
+    }
+
+    if let Some(checks) = issue.checks() {
+        field(
+            stream,
+            "Checks",
+            &humanize(&checks.state),
+            Some(result_color(&checks.state)),
+        );
+        for check in &checks.contexts.nodes {
+            print_check(stream, check);
+        }
+    }
+
+    field(stream, "Link", &issue.url, Some(Color::Blue));
+
+    if !issue.body.trim().is_empty() {
+        write(stream, "\n", None);
+        write(stream, issue.body.trim(), None);
+        write(stream, "\n", None);
+    }
+
+    for comment in &issue.comments.nodes {
+        write(stream, "\n", None);
+        write(stream, &format!("@{}", comment.author()), Some(Color::Cyan));
+        delimiter(stream);
+        write(stream, &comment.created_at, None);
+        write(stream, "\n", None);
+        write(stream, comment.body.trim(), None);
+        write(stream, "\n", None);
+    }
+
+    // The total count may be lower than the number of comments fetched, if any were deleted
+    let total: usize = issue.comments.total_count as usize;
+    let omitted: usize = total.saturating_sub(issue.comments.nodes.len());
+    if omitted > 0 {
+        write(
+            stream,
+            &format!("\n({} more comments)\n", omitted),
+            Some(Color::Yellow),
+        );
+    }
+    stream.reset().unwrap();
+}
+
+fn print_check<W: WriteColor>(stream: &mut W, check: &CheckContext) {
+    let result: &str = check.result();
+    write(stream, "  ", None);
+    write(stream, check_symbol(result), Some(result_color(result)));
+    write(stream, &format!(" {}\n", check.name()), None);
+}
+
+/// Symbol for the result of a check, or the combined result of all checks on a commit
+fn check_symbol(result: &str) -> &'static str {
+    match result {
+        "SUCCESS" => "✓",
+        "FAILURE" | "ERROR" | "TIMED_OUT" | "STARTUP_FAILURE" | "ACTION_REQUIRED" => "✗",
+        "NEUTRAL" | "SKIPPED" | "CANCELLED" | "STALE" => "-",
+        _ => "●",
+    }
+}
+
+fn field<W: WriteColor>(stream: &mut W, name: &str, value: &str, color: Option<Color>) {
+    write(stream, &format!("{}: ", name), Some(Color::Green));
+    write(stream, value, color);
+    write(stream, "\n", None);
+}
+
+/// Color for a result reported by GitHub, such as the state of a review or a check
+fn result_color(result: &str) -> Color {
+    match result {
+        "SUCCESS" | "APPROVED" => Color::Green,
+        "FAILURE" | "ERROR" | "TIMED_OUT" | "STARTUP_FAILURE" | "ACTION_REQUIRED"
+        | "CHANGES_REQUESTED" => Color::Red,
+        "NEUTRAL" | "SKIPPED" | "CANCELLED" | "STALE" | "DISMISSED" => Color::White,
+        _ => Color::Yellow,
+    }
+}
+
+/// Turn an enum value from GitHub's API, like `CHANGES_REQUESTED`, into `changes requested`
+fn humanize(value: &str) -> String {
+    value.to_lowercase().replace('_', " ")
+}
+
+fn delimiter<W: WriteColor>(stream: &mut W) {
     write(stream, " | ", Some(Color::Green));
 }
 
 fn truncate(string: String, max_length: usize) -> String {
-    let new_length: usize = std::cmp::min(string.len(), max_length);
-    if new_length < string.len() {
-        string[..new_length].to_string()
-    } else {
-        string
+    match string.char_indices().nth(max_length) {
+        Some((index, _)) => string[..index].to_string(),
+        None => string,
     }
 }
 
-fn write(stream: &mut StandardStream, content: &str, color: Option<Color>) {
+fn write<W: WriteColor>(stream: &mut W, content: &str, color: Option<Color>) {
     stream.set_color(ColorSpec::new().set_fg(color)).unwrap();
     write!(stream, "{}", content).unwrap();
 }
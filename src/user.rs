@@ -1,4 +1,4 @@
-use crate::{search::GraphQLQuery, AppErr};
+use crate::{api::Host, search::GraphQLQuery, AppErr};
 use serde::Deserialize;
 use sha2::{Digest, Sha256};
 use std::io::Write;
@@ -8,7 +8,6 @@ use std::{fs::File, str::FromStr};
 #[derive(Debug, Deserialize)]
 pub struct User {
     pub login: String,
-    pub id: String,
 }
 
 #[derive(Debug, Clone)]
@@ -20,13 +19,19 @@ impl Username {
             Some(username) => Ok(Username(username)),
             None => {
                 let username: String = api_lookup_username(token).await?.login;
-                save_username(token, &username)?;
+                save_username(token, &username).map_err(|_| AppErr::TokenWriteError)?;
                 Ok(Username(username))
             }
         }
     }
 }
 
+impl std::fmt::Display for Username {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl FromStr for Username {
     type Err = String;
 
@@ -35,12 +40,6 @@ impl FromStr for Username {
     }
 }
 
-impl From<std::io::Error> for AppErr {
-    fn from(_: std::io::Error) -> Self {
-        AppErr::TokenWriteError
-    }
-}
-
 impl From<reqwest::Error> for AppErr {
     fn from(e: reqwest::Error) -> Self {
         log::error!("Request failed {}", e);
@@ -61,10 +60,9 @@ struct Data {
 #[derive(Debug, Deserialize)]
 struct Viewer {
     login: String,
-    id: String,
 }
 
-async fn api_lookup_username(token: &str) -> Result<User, AppErr> {
+pub async fn api_lookup_username(token: &str) -> Result<User, AppErr> {
     let query = GraphQLQuery {
         variables: serde_json::Value::Null,
         query: String::from(include_str!("../data/graphql/queries/get_user.graphql")),
@@ -74,7 +72,6 @@ async fn api_lookup_username(token: &str) -> Result<User, AppErr> {
     let root: Root = crate::api::v4::request(token, query).await?;
     let user = User {
         login: root.data.viewer.login,
-        id: root.data.viewer.id,
     };
     Ok(user)
 }
@@ -94,10 +91,15 @@ fn hash_token(token: &str) -> String {
     format!("{:02x}", hasher.result())
 }
 
+/// Usernames for github.com are kept directly in the `usernames` directory, while usernames for
+/// any other host are kept in a directory for each host, since a token is only valid for one host.
 fn get_users_dir() -> PathBuf {
-    let mut path: PathBuf = dirs_next::home_dir().expect("Cannot find home dir");
-    path.push([".config", "giss", "usernames"].join("/"));
-    path
+    let host: Host = crate::api::host();
+    let dir: PathBuf = crate::settings::config_dir().join("usernames");
+    match host.is_github() {
+        true => dir,
+        false => dir.join(host.to_string()),
+    }
 }
 
 fn get_saved_username(token: &str) -> Option<String> {
@@ -0,0 +1,141 @@
+use std::{collections::HashMap, fmt, str::FromStr, time::Duration};
+
+use crate::{issue::Issue, ui, ui::DisplayConfig, AppErr};
+
+/// Interval between refreshes in watch mode, such as `30s`, `5m` or `1h`. A number without a
+/// unit is in seconds.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Interval(Duration);
+
+/// How an issue differs from when it was listed in the previous refresh
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Change {
+    New,
+    Updated,
+    Unchanged,
+}
+
+/// Repeatedly load issues with `load` and redraw them, with issues that are new or have been
+/// updated since the previous refresh highlighted. This only returns if the first load fails,
+/// later failures are shown without clearing the issues from the previous refresh.
+pub async fn watch<F>(interval: Interval, cfg: &DisplayConfig, mut load: F) -> Result<(), AppErr>
+where
+    F: FnMut() -> Result<Vec<Issue>, AppErr>,
+{
+    let mut issues: Vec<Issue> = load()?;
+    let mut changes: Vec<Change> = vec![Change::Unchanged; issues.len()];
+    let mut status: String = format!("Every {}", interval);
+
+    loop {
+        ui::redraw(&issues, &changes, &status, cfg)?;
+        tokio::time::sleep(interval.0).await;
+
+        match load() {
+            Ok(refreshed) => {
+                changes = compare(&issues, &refreshed);
+                issues = refreshed;
+                status = format!("Every {}", interval);
+            }
+            Err(e) => status = format!("Every {}, refresh failed: {:?}", interval, e),
+        }
+    }
+}
+
+/// Compare each issue in `current` to the issue with the same id in `previous`, if any
+fn compare(previous: &[Issue], current: &[Issue]) -> Vec<Change> {
+    let previous: HashMap<u64, &str> = previous
+        .iter()
+        .map(|i| (i.id, i.updated_at.as_str()))
+        .collect();
+
+    current
+        .iter()
+        .map(|issue| match previous.get(&issue.id) {
+            None => Change::New,
+            Some(updated_at) if *updated_at != issue.updated_at => Change::Updated,
+            Some(_) => Change::Unchanged,
+        })
+        .collect()
+}
+
+impl FromStr for Interval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s: &str = s.trim();
+        let split: usize = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (amount, unit): (&str, &str) = s.split_at(split);
+        let amount: u64 = amount
+            .parse::<u64>()
+            .map_err(|_| format!("Invalid interval '{}', expected for example 30s, 5m or 1h", s))?;
+        let factor: u64 = match unit {
+            "" | "s" => 1,
+            "m" => 60,
+            "h" => 60 * 60,
+            _ => return Err(format!("Invalid unit '{}' for interval, expected s, m or h", unit)),
+        };
+        match amount.checked_mul(factor) {
+            Some(0) => Err(String::from("Interval must be greater than zero")),
+            Some(seconds) => Ok(Interval(Duration::from_secs(seconds))),
+            None => Err(format!("Interval '{}' is too long", s)),
+        }
+    }
+}
+
+impl From<Interval> for Duration {
+    fn from(interval: Interval) -> Self {
+        interval.0
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds: u64 = self.0.as_secs();
+        if seconds.is_multiple_of(3600) {
+            write!(f, "{}h", seconds / 3600)
+        } else if seconds.is_multiple_of(60) {
+            write!(f, "{}m", seconds / 60)
+        } else {
+            write!(f, "{}s", seconds)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seconds(s: &str) -> Result<u64, String> {
+        Interval::from_str(s).map(|interval| Duration::from(interval).as_secs())
+    }
+
+    #[test]
+    fn units_of_interval() {
+        assert_eq!(Ok(30), seconds("30"));
+        assert_eq!(Ok(30), seconds("30s"));
+        assert_eq!(Ok(300), seconds("5m"));
+        assert_eq!(Ok(7200), seconds(" 2h "));
+    }
+
+    #[test]
+    fn invalid_intervals_are_rejected() {
+        for s in ["", "m", "-5m", "1.5h", "5d", "5 m", "0", "0h"] {
+            assert!(seconds(s).is_err(), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn too_long_intervals_are_rejected() {
+        assert_eq!(Ok(u64::MAX), seconds(&u64::MAX.to_string()));
+        assert!(seconds(&format!("{}m", u64::MAX / 60 + 1)).is_err());
+        assert!(seconds(&format!("{}h", u64::MAX)).is_err());
+        assert!(seconds("99999999999999999999999s").is_err());
+    }
+
+    #[test]
+    fn intervals_are_displayed_in_largest_whole_unit() {
+        for (s, expected) in [("45", "45s"), ("90s", "90s"), ("120", "2m"), ("60m", "1h")] {
+            assert_eq!(expected, Interval::from_str(s).unwrap().to_string());
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Golden tests, which compare the output of giss with the expected output in `tests/golden`. Run
+//! the tests with `UPDATE_GOLDEN=1` to write the current output to the golden files instead, and
+//! review the difference before committing it.
+
+mod support;
+
+use std::path::{Path, PathBuf};
+
+use support::{github, stderr, stdout, FakeServer, Giss};
+
+#[test]
+fn search_queries() {
+    let cases: &[&[&str]] = &[
+        &[],
+        &["-i"],
+        &["-p"],
+        &["-r"],
+        &["-a", "-i", "-p"],
+        &["-c"],
+        &["-o", "-c"],
+        &["-i", "-l", "bug", "-l", "good first issue"],
+        &["-i", "-P", "mantono/1"],
+        &["-i", "-P", "mantono/giss/2"],
+        &["-i", "-s", "comments", "-O", "asc"],
+        &["-i", "-s", "created"],
+        &["-i", "-s", "reactions", "-O", "descending"],
+        &["-i", "-S", "crash on start"],
+        &["-i", "-n", "250"],
+        &["-i", "--author", "bob", "--mentions", "alice", "--commenter", "carol"],
+        &["-i", "--involves", "dave", "--language", "rust"],
+        &["-p", "--reviewed-by", "erin"],
+        &["-i", "--milestone", "v1.0 beta", "--no", "assignee,label"],
+        &["-i", "--exclude-label", "wontfix", "--exclude-label", "won't fix"],
+        &["-i", "--created", ">=2021-01-01", "--updated", "2021-01-01..2021-03-31"],
+        &["-i", "--created", "<2021-01-01T12:00:00Z", "--updated", "<=2021-03-31"],
+        &["-i", "--since", "2021-01-01", "--until", "2021-02-01"],
+        &["-i", "--updated-since", "2021-01-15"],
+        &["-i", "--stale", "2021-01-01"],
+        &["-p", "--draft"],
+        &["-p", "--ready", "--review", "changes_requested", "--status", "failure"],
+        &["--review", "approved"],
+        &["-i", "--query", "team-review-requested:my-org/my-team -author:app/dependabot"],
+        &["--merged"],
+        &["-o", "--merged"],
+        &["-i", "--state-reason", "not_planned"],
+        &["--state-reason", "completed", "--author", "bob"],
+        &["--state-reason", "reopened"],
+    ];
+    let transcript: String = transcript(cases, &["--explain", "-u", "octocat", "mantono/giss"]);
+    assert_golden("search_queries", &transcript);
+}
+
+#[test]
+fn targets() {
+    let cases: &[&[&str]] = &[
+        &["mantono/giss"],
+        &["kotlin"],
+        &["@mantono"],
+        &["my-org/my.repo"],
+        &["mantono/giss", "kotlin", "my_user"],
+        &["a/b/c"],
+        &["org:kotlin"],
+        &["https://github.com/mantono/giss"],
+        &["mantono/"],
+    ];
+    let transcript: String = transcript(cases, &["--explain", "-i", "-u", "octocat"]);
+    assert_golden("targets", &search_lines(&transcript));
+}
+
+#[test]
+fn projects() {
+    let cases: &[&[&str]] = &[
+        &["-P", "mantono/1"],
+        &["-P", "mantono/giss/2"],
+        &["-P", "mantono"],
+        &["-P", "mantono/giss"],
+        &["-P", "mantono/giss/2/3"],
+    ];
+    let args: &[&str] = &["--explain", "-i", "-u", "octocat", "mantono/giss"];
+    let transcript: String = transcript(cases, args);
+    assert_golden("projects", &search_lines(&transcript));
+}
+
+#[test]
+fn output() {
+    let cases: &[&[&str]] = &[
+        &[],
+        &["--colors", "on"],
+        &["-L"],
+        &["-i", "-s", "comments", "-O", "asc"],
+        &["-u", "someone-else"],
+        &["--output", "csv"],
+        &["--output", "ndjson"],
+        &["-c"],
+        &["-c", "--colors", "on"],
+        &["-c", "--output", "ndjson"],
+        &["-c", "--output", "csv"],
+    ];
+    let transcript: String = transcript(cases, &["mantono/giss"]);
+    assert_golden("output", &transcript);
+}
+
+/// Run giss with each of `cases` followed by `common`, against a fake API with the default
+/// responses, and write the command line, stdout and stderr of each run
+fn transcript(cases: &[&[&str]], common: &[&str]) -> String {
+    let server = FakeServer::start(github);
+    let mut transcript = String::new();
+    for case in cases {
+        let args: Vec<&str> = case.iter().chain(common).copied().collect();
+        let output = Giss::new(&server).run(&args);
+        transcript.push_str(&format!("$ giss {}\n", quote(&args)));
+        transcript.push_str(&stdout(&output));
+        transcript.push_str(&stderr(&output));
+        transcript.push('\n');
+    }
+    transcript
+}
+
+/// Only keep the command lines, search queries and errors of a transcript
+fn search_lines(transcript: &str) -> String {
+    transcript
+        .lines()
+        .filter(|line| !line.starts_with(' ') && !line.starts_with('}'))
+        .filter(|line| !line.starts_with("SearchIssues:"))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+fn quote(args: &[&str]) -> String {
+    args.iter()
+        .map(|arg| match arg.contains(|c: char| c.is_whitespace() || "'\"<>".contains(c)) {
+            true => format!("'{}'", arg),
+            false => arg.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn assert_golden(name: &str, actual: &str) {
+    let path: PathBuf = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.txt", name));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected: String = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("No golden file {:?} ({}), run with UPDATE_GOLDEN=1", path, e));
+    if expected != actual {
+        let diff: String = expected
+            .lines()
+            .zip(actual.lines())
+            .enumerate()
+            .find(|(_, (e, a))| e != a)
+            .map(|(i, (e, a))| format!("line {}:\n  expected: {}\n  actual:   {}", i + 1, e, a))
+            .unwrap_or_else(|| String::from("the number of lines differ"));
+        panic!("Output differs from {:?} at {}\n\nActual output:\n{}", path, diff, actual);
+    }
+}
@@ -0,0 +1,207 @@
+mod support;
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::Value;
+use support::{fixture, github, stderr, stdout, FakeServer, Giss, Response};
+
+#[test]
+fn lists_issues_and_pull_requests_for_user_of_token() {
+    let server = FakeServer::start(github);
+    let output = Giss::new(&server).run(&["mantono/giss"]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let stdout: String = stdout(&output);
+    assert!(stdout.contains("#12 mantono/giss"), "{}", stdout);
+    assert!(stdout.contains("#7 kotlin/kotlinx.cli"), "{}", stdout);
+    assert!(stdout.contains("#13 mantono/giss"), "{}", stdout);
+    assert!(stdout.contains("#14 mantono/giss"), "{}", stdout);
+    assert_eq!(4, stdout.lines().count(), "{}", stdout);
+
+    assert_eq!(1, server.operations("GetUser").len());
+    let searches: Vec<String> = server
+        .operations("SearchIssues")
+        .iter()
+        .map(|r| r.search_query())
+        .collect();
+    assert_eq!(3, searches.len());
+    assert!(searches.iter().any(|q| q.starts_with("type:issue ")));
+    assert!(searches.iter().any(|q| q.starts_with("type:pr state:")));
+    assert!(searches.iter().any(|q| q.starts_with("type:pr review-requested:octocat ")));
+
+    for request in server.requests() {
+        assert_eq!("POST", request.method);
+        assert_eq!("/graphql", request.path);
+        assert_eq!(Some("Bearer test-token"), request.header("authorization"));
+    }
+}
+
+#[test]
+fn username_of_token_is_only_looked_up_once() {
+    let server = FakeServer::start(github);
+    let giss = Giss::new(&server);
+    giss.run(&["-i", "mantono/giss"]);
+    let output = giss.run(&["-i", "mantono/giss", "--refresh"]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(1, server.operations("GetUser").len());
+    assert_eq!(2, server.operations("SearchIssues").len());
+}
+
+#[test]
+fn global_options_can_be_given_before_list_subcommand() {
+    let server = FakeServer::start(github);
+    let args = ["--timeout", "5s", "list", "-i", "-u", "octocat", "mantono/giss"];
+    let output = Giss::new(&server).run(&args);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("#12 mantono/giss"), "{}", stdout(&output));
+}
+
+#[test]
+fn list_options_before_subcommand_are_rejected() {
+    let server = FakeServer::start(github);
+    let output = Giss::new(&server).run(&["-u", "octocat", "-i", "mantono/giss", "list"]);
+
+    assert!(!output.status.success());
+    let stderr: String = stderr(&output);
+    assert!(stderr.contains("must be given after the subcommand"), "{}", stderr);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn queries_are_explained_without_token_or_user() {
+    let server = FakeServer::start(github);
+    let output = Giss::new(&server).without_token().run(&["--explain", "mantono/giss"]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let stdout: String = stdout(&output);
+    assert!(stdout.contains("type:issue state:open"), "{}", stdout);
+    assert!(!stdout.contains("review-requested"), "{}", stdout);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn all_targets_are_searched_in_one_query() {
+    let server = FakeServer::start(github);
+    let output = Giss::new(&server).run(&["-i", "-u", "octocat", "mantono/giss", "kotlin"]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(server.operations("GetUser").is_empty());
+    let searches = server.operations("SearchIssues");
+    assert_eq!(1, searches.len());
+    assert!(searches[0].search_query().contains("repo:mantono/giss org:kotlin"));
+}
+
+#[test]
+fn pages_are_fetched_until_limit_is_reached() {
+    let server = FakeServer::start(|request| {
+        let mut body: Value = serde_json::from_str(&fixture("search_issues")).unwrap();
+        if request.body["variables"]["cursor"].is_null() {
+            body["data"]["search"]["pageInfo"]["hasNextPage"] = Value::Bool(true);
+        }
+        Response::json(&body.to_string())
+    });
+    let output = Giss::new(&server).run(&["-i", "-u", "octocat", "-n", "3", "mantono/giss"]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let searches = server.operations("SearchIssues");
+    assert_eq!(2, searches.len());
+    assert_eq!(3, searches[0].body["variables"]["limit"]);
+    assert_eq!(1, searches[1].body["variables"]["limit"]);
+    assert_eq!("Y3Vyc29yOjI=", searches[1].body["variables"]["cursor"]);
+}
+
+#[test]
+fn server_errors_are_logged() {
+    let server = FakeServer::start(|_| Response::status(502, "{\"message\":\"Bad Gateway\"}"));
+    let output = Giss::new(&server).run(&["-i", "-u", "octocat", "mantono/giss"]);
+
+    assert_eq!("", stdout(&output));
+    assert!(stderr(&output).contains("GitHub API: 502"), "{}", stderr(&output));
+}
+
+#[test]
+fn graphql_errors_are_logged() {
+    let server = FakeServer::start(|_| Response::json(&fixture("search_not_found")));
+    let output = Giss::new(&server).run(&["-i", "-u", "octocat", "mantono/missing"]);
+
+    assert_eq!("", stdout(&output));
+    let stderr: String = stderr(&output);
+    assert!(stderr.contains("Could not resolve to a Repository"), "{}", stderr);
+}
+
+#[test]
+fn requests_time_out() {
+    let server = FakeServer::start(|request| github(request).delay(Duration::from_secs(3)));
+    let args = ["-i", "-u", "octocat", "--timeout", "1s", "mantono/giss"];
+    let output = Giss::new(&server).run(&args);
+
+    assert_eq!("", stdout(&output));
+    assert!(stderr(&output).contains("timed out"), "{}", stderr(&output));
+}
+
+#[test]
+fn requests_are_retried_when_secondary_rate_limit_is_exceeded() {
+    let count = AtomicUsize::new(0);
+    let server = FakeServer::start(move |request| match count.fetch_add(1, Ordering::SeqCst) {
+        0 => Response::status(403, "{\"message\":\"You have exceeded a secondary rate limit.\"}")
+            .header("Retry-After", "1"),
+        _ => github(request),
+    });
+    let output = Giss::new(&server).run(&["-i", "-u", "octocat", "mantono/giss"]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("#12 mantono/giss"), "{}", stdout(&output));
+    assert_eq!(2, server.operations("SearchIssues").len());
+}
+
+#[test]
+fn requests_are_retried_when_rate_limited_in_response() {
+    let count = AtomicUsize::new(0);
+    let server = FakeServer::start(move |request| match count.fetch_add(1, Ordering::SeqCst) {
+        0 => Response::json(&fixture("rate_limited"))
+            .header("X-RateLimit-Remaining", "0")
+            .header("X-RateLimit-Reset", &now().to_string()),
+        _ => github(request),
+    });
+    let output = Giss::new(&server).run(&["-i", "-u", "octocat", "mantono/giss"]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("#12 mantono/giss"), "{}", stdout(&output));
+    assert_eq!(2, server.operations("SearchIssues").len());
+}
+
+#[test]
+fn requests_are_not_retried_when_rate_limit_resets_later() {
+    let server = FakeServer::start(|_| {
+        Response::status(403, "{\"message\":\"API rate limit exceeded\"}")
+            .header("X-RateLimit-Limit", "5000")
+            .header("X-RateLimit-Remaining", "0")
+            .header("X-RateLimit-Reset", &(now() + 3600).to_string())
+    });
+    let output = Giss::new(&server).run(&["-i", "-u", "octocat", "mantono/giss"]);
+
+    assert_eq!("", stdout(&output));
+    assert!(stderr(&output).contains("rate limited, try again in"), "{}", stderr(&output));
+    assert_eq!(1, server.operations("SearchIssues").len());
+}
+
+#[test]
+fn cached_results_are_used_offline() {
+    let server = FakeServer::start(github);
+    let giss = Giss::new(&server);
+    let online = giss.run(&["-i", "-u", "octocat", "mantono/giss"]);
+    let offline = giss.run(&["-i", "-u", "octocat", "mantono/giss", "--offline"]);
+
+    assert!(offline.status.success(), "{}", stderr(&offline));
+    assert_eq!(stdout(&online), stdout(&offline));
+    assert_eq!(1, server.requests().len());
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
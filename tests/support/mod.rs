@@ -0,0 +1,286 @@
+//! A fake GitHub API, which is served in-process on a local port, and helpers for running giss
+//! against it.
+
+// Each test file uses its own subset of the helpers
+#![allow(dead_code)]
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use serde_json::Value;
+use tempfile::TempDir;
+
+/// A request received by the fake server
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Value,
+}
+
+impl Request {
+    /// Name of the GraphQL operation, such as `SearchIssues` or `GetUser`
+    pub fn operation(&self) -> String {
+        let query: &str = self.body["query"].as_str().unwrap_or_default();
+        query
+            .split_whitespace()
+            .skip_while(|word| *word != "query" && *word != "mutation")
+            .nth(1)
+            .map(|name| name.split(['(', '{']).next().unwrap_or_default().to_string())
+            .unwrap_or_default()
+    }
+
+    /// The search query of a `SearchIssues` operation
+    pub fn search_query(&self) -> String {
+        self.body["variables"]["searchQuery"].as_str().unwrap_or_default().to_string()
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A response for the fake server to send
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    /// Time to wait before the response is sent
+    pub delay: Duration,
+}
+
+impl Response {
+    pub fn json(body: &str) -> Response {
+        Response {
+            status: 200,
+            headers: Vec::new(),
+            body: body.to_string(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn status(status: u16, body: &str) -> Response {
+        Response {
+            status,
+            ..Response::json(body)
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Response {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn delay(self, delay: Duration) -> Response {
+        Response { delay, ..self }
+    }
+}
+
+/// Read a recorded response from `tests/fixtures`
+pub fn fixture(name: &str) -> String {
+    let path: PathBuf = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(format!("{}.json", name));
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("No fixture {:?}: {}", path, e))
+}
+
+/// The default responses, with a user for `GetUser`, issues for searches of issues and pull
+/// requests, of which one has a review request for the user, for searches of pull requests.
+/// Searches for closed tickets get both issues and pull requests that are closed or merged.
+pub fn github(request: &Request) -> Response {
+    match request.operation().as_str() {
+        "GetUser" => Response::json(&fixture("get_user")),
+        "SearchIssues" if request.search_query().contains("state:closed") => {
+            Response::json(&fixture("search_closed"))
+        }
+        "SearchIssues" if request.search_query().contains("type:issue") => {
+            Response::json(&fixture("search_issues"))
+        }
+        "SearchIssues" => Response::json(&fixture("search_pull_requests")),
+        operation => Response::status(400, &format!("Unexpected operation '{}'", operation)),
+    }
+}
+
+type Handler = dyn Fn(&Request) -> Response + Send + Sync;
+
+/// A fake GitHub API, which answers each request with the response given by its handler, and
+/// keeps all requests so they can be inspected
+pub struct FakeServer {
+    url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl FakeServer {
+    pub fn start<F>(handler: F) -> FakeServer
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Unable to bind fake server");
+        let url: String = format!("http://{}", listener.local_addr().unwrap());
+        let requests: Arc<Mutex<Vec<Request>>> = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let received = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (handler, received) = (handler.clone(), received.clone());
+                thread::spawn(move || serve(stream, handler.as_ref(), &received));
+            }
+        });
+
+        FakeServer { url, requests }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Requests for the GraphQL operation `name`
+    pub fn operations(&self, name: &str) -> Vec<Request> {
+        self.requests().into_iter().filter(|r| r.operation() == name).collect()
+    }
+}
+
+/// Serve requests on a connection until it is closed by the client
+fn serve(stream: TcpStream, handler: &Handler, received: &Mutex<Vec<Request>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    while let Some(request) = read_request(&mut reader) {
+        received.lock().unwrap().push(request.clone());
+        let response: Response = handler(&request);
+        thread::sleep(response.delay);
+        if write_response(&mut writer, &response).is_err() {
+            return;
+        }
+    }
+}
+
+fn read_request(reader: &mut BufReader<TcpStream>) -> Option<Request> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = (parts.next()?.to_string(), parts.next()?.to_string());
+
+    let mut headers: Vec<(String, String)> = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        match line.trim_end().split_once(':') {
+            Some((name, value)) => headers.push((name.to_string(), value.trim().to_string())),
+            None => break,
+        }
+    }
+
+    let length: usize = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    let mut body: Vec<u8> = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    Some(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+fn write_response(writer: &mut TcpStream, response: &Response) -> std::io::Result<()> {
+    let mut head: String = format!(
+        "HTTP/1.1 {} Fake\r\nContent-Type: application/json\r\nContent-Length: {}\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    writer.write_all(head.as_bytes())?;
+    writer.write_all(response.body.as_bytes())?;
+    writer.flush()
+}
+
+/// An isolated environment to run giss in, with its own home, configuration and cache
+pub struct Giss {
+    home: TempDir,
+    api_url: String,
+    token: Option<String>,
+}
+
+impl Giss {
+    pub fn new(server: &FakeServer) -> Giss {
+        Giss {
+            home: tempfile::tempdir().unwrap(),
+            api_url: server.url().to_string(),
+            token: Some(String::from("test-token")),
+        }
+    }
+
+    /// Run without any token, as for a user that is not logged in
+    pub fn without_token(self) -> Giss {
+        Giss {
+            token: None,
+            ..self
+        }
+    }
+
+    /// Run giss with `args`, without colors unless `--colors` is given. The directories are
+    /// given explicitly, since the home directory is not taken from `HOME` on all platforms.
+    pub fn run(&self, args: &[&str]) -> Output {
+        let home: &Path = self.home.path();
+        let colors: &[&str] = match args.contains(&"--colors") {
+            true => &[],
+            false => &["--colors", "off"],
+        };
+        let mut command = Command::new(env!("CARGO_BIN_EXE_giss"));
+        command
+            .args(args)
+            .args(colors)
+            .current_dir(home)
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .env("HOME", home)
+            .env("XDG_CACHE_HOME", home.join("cache"))
+            .env("XDG_CONFIG_HOME", home.join("config"))
+            .env("GISS_CACHE_DIR", home.join("cache").join("giss"))
+            .env("GISS_CONFIG_DIR", home.join(".config").join("giss"))
+            .env("GH_CONFIG_DIR", home.join("config").join("gh"))
+            .env("GISS_API_URL", &self.api_url);
+        if let Some(token) = &self.token {
+            command.env("GITHUB_TOKEN", token);
+        }
+        // Windows needs SystemRoot for networking and other system services
+        if let Some(root) = std::env::var_os("SystemRoot") {
+            command.env("SystemRoot", root);
+        }
+        command.output().expect("Unable to run giss")
+    }
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}